@@ -1,5 +1,4 @@
 use crate::opcodes::Op;
-use std::usize;
 pub type Value = f64;
 pub struct Chunk {
     pub code: Vec<u8>,
@@ -69,7 +68,7 @@ impl Chunk {
             Op::ConstantLong => self.print_constant_long_instruction(opcode, offset),
             _default => {
                 println!("{:?}", opcode);
-                return offset + 1;
+                offset + 1
             }
         }
     }
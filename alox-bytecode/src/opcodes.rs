@@ -31,7 +31,7 @@ impl TryFrom<u8> for Op {
         if value > Op::Negate as u8 {
             Err(())
         } else {
-            unsafe { Ok(core::mem::transmute(value)) }
+            unsafe { Ok(core::mem::transmute::<u8, Op>(value)) }
         }
     }
 }
\ No newline at end of file
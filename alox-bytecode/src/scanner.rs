@@ -18,8 +18,8 @@ impl<'a> Scanner<'a> {
     }
 
     pub fn scan(&mut self) {
+        let mut line = 0;
         loop {
-            let mut line = 0;
             let token = self.scan_token();
             if token.line != line || token.line == 0 {
                 print!("{}", token.line);
@@ -34,7 +34,7 @@ impl<'a> Scanner<'a> {
         }
     }
 
-    fn scan_token(&mut self) -> Token {
+    fn scan_token(&mut self) -> Token<'a> {
         self.skip_whitespace();
         self.start = self.current;
 
@@ -127,7 +127,7 @@ impl<'a> Scanner<'a> {
     }
 
     /// Checks the next token, if matches makes the token kind specified by opt1, op2 otherwise
-    fn match_next_token(&mut self, expected: u8, opt1: TokenKind, opt2: TokenKind) -> Token {
+    fn match_next_token(&mut self, expected: u8, opt1: TokenKind, opt2: TokenKind) -> Token<'a> {
         if self.match_next(expected) {
             self.make_token(opt1)
         } else {
@@ -135,7 +135,7 @@ impl<'a> Scanner<'a> {
         }
     }
 
-    fn make_token(&self, kind: TokenKind) -> Token {
+    fn make_token(&self, kind: TokenKind) -> Token<'a> {
         Token::make(
             self.source,
             kind,
@@ -145,7 +145,7 @@ impl<'a> Scanner<'a> {
         )
     }
 
-    fn identifier(&mut self) -> Token {
+    fn identifier(&mut self) -> Token<'a> {
         while is_alpha(self.peek()) || self.peek().is_ascii_digit() {
             self.advance();
         }
@@ -201,24 +201,72 @@ impl<'a> Scanner<'a> {
         }
     }
 
-    fn number(&mut self) -> Token {
-        while self.peek().is_ascii_digit() {
+    /// Scans decimal integers and floats (`1_000`, `6.02e23`) as well as hexadecimal,
+    /// octal and binary integers (`0xFF`, `0o17`, `0b1010`). Underscores may separate digits.
+    fn number(&mut self) -> Token<'a> {
+        let first = self.source.as_bytes()[self.start];
+        let radix = match self.peek() {
+            b'x' | b'X' if first == b'0' => Some(16),
+            b'o' | b'O' if first == b'0' => Some(8),
+            b'b' | b'B' if first == b'0' => Some(2),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            // consume the base prefix
             self.advance();
+            return self.radix_number(radix);
         }
 
+        self.digits();
+
         if self.peek() == b'.' && self.peek_next().is_ascii_digit() {
             // consume the "."
             self.advance();
+            self.digits();
+        }
 
-            while self.peek().is_ascii_digit() {
+        if self.peek() == b'e' || self.peek() == b'E' {
+            self.advance();
+            if self.peek() == b'+' || self.peek() == b'-' {
                 self.advance();
             }
+            if !self.peek().is_ascii_digit() {
+                return Token::error("Missing exponent digits in number.", self.line);
+            }
+            self.digits();
+        }
+
+        if !separators_between_digits(&self.source[self.start..self.current], 10) {
+            return Token::error("'_' must separate digits in number.", self.line);
+        }
+        self.make_token(TokenKind::Number)
+    }
+
+    fn radix_number(&mut self, radix: u32) -> Token<'a> {
+        while self.peek().is_ascii_alphanumeric() || self.peek() == b'_' {
+            self.advance();
         }
 
+        let body = &self.source[self.start + 2..self.current];
+        if body.bytes().all(|b| b == b'_') {
+            return Token::error("Missing digits after number base prefix.", self.line);
+        }
+        if body.chars().any(|c| c != '_' && !c.is_digit(radix)) {
+            return Token::error("Invalid digit for number base.", self.line);
+        }
+        if !separators_between_digits(body, radix) {
+            return Token::error("'_' must separate digits in number.", self.line);
+        }
         self.make_token(TokenKind::Number)
     }
 
-    fn string(&mut self) -> Token {
+    fn digits(&mut self) {
+        while self.peek().is_ascii_digit() || self.peek() == b'_' {
+            self.advance();
+        }
+    }
+
+    fn string(&mut self) -> Token<'a> {
         while self.peek() != b'"' && !self.is_at_end() {
             if self.peek() == b'\n' {
                 self.line += 1;
@@ -245,6 +293,75 @@ impl<'a> Scanner<'a> {
     }
 }
 
+/// Digit separators must sit between two digits: `1_000` is fine, `1__0`, `1_` and `1_.5` are not.
+fn separators_between_digits(literal: &str, radix: u32) -> bool {
+    let bytes = literal.as_bytes();
+    bytes.iter().enumerate().all(|(i, b)| {
+        if *b != b'_' {
+            return true;
+        }
+        // a prefixed literal may start with a separator, as in `0x_FF`
+        let before = match i.checked_sub(1) {
+            Some(before) => (bytes[before] as char).is_digit(radix),
+            None => radix != 10,
+        };
+        let after = bytes
+            .get(i + 1)
+            .is_some_and(|after| (*after as char).is_digit(radix));
+        before && after
+    })
+}
+
 fn is_alpha(char: u8) -> bool {
     char.is_ascii_alphabetic() || char == b'_'
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Scanner;
+    use crate::token::{Token, TokenKind};
+
+    fn scan(source: &str) -> Token<'_> {
+        Scanner::new(source).scan_token()
+    }
+
+    #[test]
+    fn number_literals() {
+        for source in &[
+            "42",
+            "1_000",
+            "3.14",
+            "6.02e23",
+            "1E-9",
+            "2.5e+3",
+            "0xFF",
+            "0x_dead_BEEF",
+            "0o17",
+            "0b1010",
+            "0b_1111_0000",
+        ] {
+            let token = scan(source);
+            assert!(matches!(token.kind, TokenKind::Number), "{}", source);
+            assert_eq!(token.lexeme, *source);
+        }
+    }
+
+    #[test]
+    fn malformed_number_literals() {
+        for (source, msg) in &[
+            ("0x", "Missing digits after number base prefix."),
+            ("0b_", "Missing digits after number base prefix."),
+            ("1e", "Missing exponent digits in number."),
+            ("1e+", "Missing exponent digits in number."),
+            ("0o8", "Invalid digit for number base."),
+            ("0xFG", "Invalid digit for number base."),
+            ("1__0", "'_' must separate digits in number."),
+            ("1_", "'_' must separate digits in number."),
+            ("0x_FF_", "'_' must separate digits in number."),
+        ] {
+            let token = scan(source);
+            assert!(matches!(token.kind, TokenKind::Error), "{}", source);
+            assert_eq!(token.lexeme, *msg, "{}", source);
+        }
+    }
+}
@@ -38,7 +38,7 @@ impl Vm {
     }
 
     fn run(&mut self) -> InterpreterResult {
-        Ok(loop {
+        loop {
             if self.ip >= self.chunk.code.len() {
                 break;
             }
@@ -63,7 +63,8 @@ impl Vm {
                 Op::Multiply => binary_op!(self, *),
                 Op::Divide => binary_op!(self, /)
             }
-        })
+        }
+        Ok(())
     }
 
     fn peek(&mut self) -> &mut Value {
@@ -66,10 +66,7 @@ fn transform_definition(input: Definition) -> TokenStream {
             let struct_name = seperated[0];
             let fields = seperated[1];
             structs.push(Ident::new(struct_name, struct_name.span()));
-            non_base_types.push(Ident::new(
-                &struct_name.to_ascii_lowercase(),
-                struct_name.to_ascii_lowercase().span(),
-            ));
+            non_base_types.push(Ident::new(&to_snake_case(struct_name), struct_name.span()));
             output.extend(define_struct_type(&base_name, struct_name, fields));
         } else {
             let new_struct = _type.trim();
@@ -123,6 +120,17 @@ fn transform_definition(input: Definition) -> TokenStream {
     output
 }
 
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
 fn get_plural(base_name: &str) -> String {
     if base_name.ends_with("pr") {
         format!("{}essions", base_name)
@@ -133,7 +141,7 @@ fn get_plural(base_name: &str) -> String {
 
 fn define_struct_type(base_name: &str, struct_name: &str, fields: &str) -> TokenStream {
     let fields: Vec<&str> = fields.split(',').map(|s| s.trim_start()).collect();
-    let struct_name = Ident::new(&struct_name, struct_name.span());
+    let struct_name = Ident::new(struct_name, struct_name.span());
     let mut names: Vec<Ident> = Vec::new();
     let mut types: Vec<Type> = Vec::new();
 
@@ -154,7 +162,7 @@ fn define_struct_type(base_name: &str, struct_name: &str, fields: &str) -> Token
 fn define_enum_type(base_name: &str, enum_name: &str, variants: &str) -> TokenStream {
     let variants: Vec<&str> = variants.split(',').map(|s| s.trim_start()).collect();
 
-    let enum_name = Ident::new(&enum_name, enum_name.span());
+    let enum_name = Ident::new(enum_name, enum_name.span());
     let mut names = Vec::new();
     let mut types = Vec::new();
     variants.iter().for_each(|v| {
@@ -1,19 +1,45 @@
-use std::{fmt::Display, rc::Rc};
+// the node constructors generated by `ast_gen!` take every field of the node
+#![allow(clippy::too_many_arguments)]
 
-use crate::{error::RuntimeException, functions::Callable, token::Token};
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+use crate::{
+    classes::{AloxClass, AloxInstance, AloxTrait, Constructor},
+    enums::EnumType,
+    error::RuntimeException,
+    functions::Callable,
+    generators::Generator,
+    modules::Module,
+    token::Token,
+};
 use ast_gen::ast_gen;
 
 ast_gen!(
     "~Expr",
     [
         "Nil",
-        "Value      / String String, f64 Number, Nil Nil, bool Bool",
+        "Value      / String String, f64 Number, i64 Integer, Nil Nil, bool Bool, ErrorValue Error, ListValue List, MapValue Map, RangeValue Range, VariantValue Variant, InstanceValue Instance",
         "Assign     : Token name, Expr value",
         "Binary     : Expr left, Token operator, Expr right",
-        "Call       : Expr callee, Token paren, Expr* arguments",
+        "Call       : Expr callee, Token paren, Expr* arguments, bool optional",
+        "CompoundAssign : Expr target, Token operator, Expr value",
+        "Conditional: Expr condition, Expr then_branch, Expr else_branch",
+        "DestructureAssign : Pattern pattern, Token equals, Expr value",
+        "Get        : Expr object, Token name, bool optional",
         "Grouping   : Expr expression",
+        "Index      : Expr object, Token bracket, Expr index",
+        "Increment  : Expr target, Token operator, bool prefix",
+        "List       : Token bracket, Expr* elements",
         "Literal    : Value value",
         "Logical    : Expr left, Token operator, Expr right",
+        "OptionalChain : Expr expression",
+        "Map        : Token brace, Expr* keys, Expr* values",
+        "Match_     : Token keyword, Expr subject, ExprArm* arms",
+        "Range      : Expr start, Token operator, Expr end",
+        "Set        : Expr object, Token name, Expr value",
+        "SetIndex   : Expr object, Token bracket, Expr index, Expr value",
+        "Super_     : Token keyword, Token method",
+        "This       : Token keyword",
         "Unary      : Token operator, Expr right",
         "Variable   : Token name"
     ]
@@ -23,16 +49,223 @@ ast_gen!(
     "~Stmt",
     [
         "Expression : Expr expression",
-        "Function   : Token name, Token* params, Stmt* body",
+        "Function   : Token name, Token* params, Expr* defaults, Token? rest, Stmt* body, bool generator, Annotation* param_types, Token? return_type, Expr* decorators",
         "If_        : Expr condition, Stmt then_branch, Stmt? else_branch",
         "Print      : Expr expression",
         "While_     : Expr condition, Stmt body",
-        "Return_    : Token keyword, Expr? value",
-        "Var        : Token name, Expr? initializer",
+        "ForIn      : Token name, Expr iterable, Stmt body",
+        "Return_    : Token keyword, Expr? value, bool tail_call",
+        "Yield_     : Token keyword, Expr? value",
+        "Throw      : Token keyword, Expr value",
+        "Defer      : Token keyword, Expr expression",
+        "Try_       : Token keyword, Stmt* body, Token? catch_name, Stmt* catch_body, Stmt* finally_body",
+        "Var        : Token name, Expr? initializer, bool constant, Token? annotation",
+        "Destructure: Pattern pattern, Expr initializer, bool constant",
         "Block      : Stmt* statements",
+        "Import     : Token keyword, Token path, Token? alias, Token* names",
+        "Enum_      : Token name, EnumVariant* variants",
+        "Class      : Token name, Expr? superclass, Expr* traits, Function* methods, Function* class_methods, Function* getters, Function* setters",
+        "Trait_     : Token name, Function* methods",
+        "MatchStmt  : Token keyword, Expr subject, StmtArm* arms",
+        "Switch     : Token keyword, Expr subject, SwitchCase* cases, Stmt? default_branch",
     ]
 );
 
+/// The optional `: Type` after a parameter name, checked before the program runs.
+pub type Annotation = Option<Token>;
+
+/// The target of a destructuring declaration or assignment: `[a, b]` unpacks a list
+/// by position and `{x, y}` unpacks a map (or any object's properties) by name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    List { bracket: Token, names: Vec<Token> },
+    Map { brace: Token, names: Vec<Token> },
+}
+
+impl Pattern {
+    pub fn names(&self) -> &[Token] {
+        match self {
+            Pattern::List { names, .. } | Pattern::Map { names, .. } => names,
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = self
+            .names()
+            .iter()
+            .map(|name| name.lexeme.as_str())
+            .collect();
+        match self {
+            Pattern::List { .. } => write!(f, "[{}]", names.join(", ")),
+            Pattern::Map { .. } => write!(f, "{{{}}}", names.join(", ")),
+        }
+    }
+}
+
+/// One variant of an `enum` declaration, like `Rect(w, h)`, naming its fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariant {
+    pub name: Token,
+    pub fields: Vec<Token>,
+}
+
+/// A `pattern => body` arm of a `match`, whose body is an expression in a `match`
+/// expression and a statement in a `match` statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arm<T> {
+    pub pattern: MatchPattern,
+    pub body: T,
+}
+
+pub type ExprArm = Arm<Expr>;
+pub type StmtArm = Arm<Stmt>;
+
+/// A `case 1, 2: ...` of a `switch`, whose statements form one block.
+#[derive(Debug, Clone)]
+pub struct SwitchCase {
+    pub keyword: Token,
+    pub values: Vec<Expr>,
+    pub body: Stmt,
+}
+
+/// What a `match` arm compares its subject against.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchPattern {
+    /// `_` matches anything.
+    Wildcard(Token),
+    /// `1`, `"x"`, `true` or `nil` matches an equal value.
+    Literal(Token, Value),
+    /// A name matches a unit variant of that name if one is in scope,
+    /// and otherwise matches anything and binds it to the name.
+    Identifier(Token),
+    /// `Rect(w, _)` matches a `Rect` whose fields match the inner patterns.
+    Variant {
+        name: Token,
+        fields: Vec<MatchPattern>,
+    },
+}
+
+impl MatchPattern {
+    /// The token the pattern starts with, for error lines.
+    pub fn token(&self) -> &Token {
+        match self {
+            MatchPattern::Wildcard(token)
+            | MatchPattern::Literal(token, _)
+            | MatchPattern::Identifier(token)
+            | MatchPattern::Variant { name: token, .. } => token,
+        }
+    }
+}
+
+impl Display for MatchPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchPattern::Wildcard(_) => write!(f, "_"),
+            MatchPattern::Literal(_, value) => write!(f, "{}", value.repr()),
+            MatchPattern::Identifier(name) => write!(f, "{}", name.lexeme),
+            MatchPattern::Variant { name, fields } => {
+                let fields: Vec<String> = fields.iter().map(ToString::to_string).collect();
+                write!(f, "{}({})", name.lexeme, fields.join(", "))
+            }
+        }
+    }
+}
+
+/// A runtime error caught by a `catch` clause, exposing `message` and `line` to Lox code.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct ErrorValue {
+    pub message: String,
+    pub line: Option<usize>,
+}
+
+/// Lists and maps are shared by reference, so every variable holding one sees the same elements.
+pub type ListValue = Rc<RefCell<Vec<Value>>>;
+/// Map entries in insertion order, with keys compared like `==`.
+pub type MapValue = Rc<RefCell<Vec<(Value, Value)>>>;
+
+/// A value of an enum variant, like `Circle(2)`.
+#[derive(Debug, PartialEq)]
+pub struct EnumValue {
+    pub enum_name: String,
+    pub variant: String,
+    pub fields: Vec<Value>,
+}
+
+pub type VariantValue = Rc<EnumValue>;
+
+/// Instances are shared by reference and compare equal only to themselves.
+pub type InstanceValue = Rc<AloxInstance>;
+
+/// The integers from `start` up to `end`, which is only included for `a..=b`.
+#[derive(Debug, Clone, Copy, PartialEq, new)]
+pub struct RangeValue {
+    pub start: i64,
+    pub end: i64,
+    pub inclusive: bool,
+}
+
+impl RangeValue {
+    /// The integers in the range, as an inclusive range so `a..=b` can reach `i64::MAX`.
+    pub fn values(&self) -> std::ops::RangeInclusive<i64> {
+        if self.inclusive {
+            return self.start..=self.end;
+        }
+        match self.end.checked_sub(1) {
+            Some(last) => self.start..=last,
+            // nothing is below `i64::MIN`
+            None => std::ops::RangeInclusive::new(1, 0),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(val) => write!(f, "{}", val),
+            // floats always show a fractional part, so `3.0` never prints like the integer `3`
+            Value::Number(val) => write!(f, "{:?}", val),
+            Value::Integer(val) => write!(f, "{}", val),
+            Value::Nil(_) => write!(f, "Nil"),
+            Value::Bool(val) => write!(f, "{}", val),
+            Value::Error(err) => write!(f, "{}", err.message),
+            Value::List(list) => {
+                let elements: Vec<String> = list.borrow().iter().map(Value::repr).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Value::Map(map) => {
+                let entries: Vec<String> = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.repr(), value.repr()))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Value::Range(range) if range.inclusive => write!(f, "{}..={}", range.start, range.end),
+            Value::Range(range) => write!(f, "{}..{}", range.start, range.end),
+            Value::Variant(variant) if variant.fields.is_empty() => {
+                write!(f, "{}", variant.variant)
+            }
+            Value::Variant(variant) => {
+                let fields: Vec<String> = variant.fields.iter().map(Value::repr).collect();
+                write!(f, "{}({})", variant.variant, fields.join(", "))
+            }
+            Value::Instance(instance) => write!(f, "{}", instance),
+        }
+    }
+}
+
+impl Value {
+    /// How a value is shown inside a list or map, where strings keep their quotes.
+    fn repr(&self) -> String {
+        match self {
+            Value::String(val) => format!("{:?}", val),
+            val => val.to_string(),
+        }
+    }
+}
+
 pub type AloxObjResult = Result<AloxObject, RuntimeException>;
 pub type ValueResult = Result<Value, RuntimeException>;
 
@@ -40,7 +273,12 @@ pub type ValueResult = Result<Value, RuntimeException>;
 pub enum AloxObject {
     Value(Value),
     Function(Rc<dyn Callable>),
-    Expr(Expr),
+    Module(Rc<Module>),
+    Enum(Rc<EnumType>),
+    Class(Rc<AloxClass>),
+    Trait(Rc<AloxTrait>),
+    Generator(Rc<RefCell<Generator>>),
+    Expr(Box<Expr>),
 }
 
 impl AloxObject {
@@ -56,13 +294,44 @@ impl AloxObject {
     }
 
     pub fn to_function(self, callee: &Call) -> Result<Rc<dyn Callable>, RuntimeException> {
-        if let AloxObject::Function(func) = self {
-            Ok(func)
-        } else {
-            Err(RuntimeException::ExpectedFunction {
+        self.to_callable()
+            .ok_or_else(|| RuntimeException::ExpectedFunction {
                 line: callee.paren.line,
                 lexeme: callee.paren.lexeme.clone(),
             })
+    }
+
+    /// What calling this object calls, if it can be called.
+    pub fn to_callable(self) -> Option<Rc<dyn Callable>> {
+        match self {
+            AloxObject::Function(func) => Some(func),
+            // calling a class constructs an instance of it
+            AloxObject::Class(class) => Some(Rc::new(Constructor(class))),
+            _ => None,
+        }
+    }
+
+    /// The kind of object, as named in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            AloxObject::Value(Value::String(_)) => "string",
+            AloxObject::Value(Value::Number(_)) => "number",
+            AloxObject::Value(Value::Integer(_)) => "integer",
+            AloxObject::Value(Value::Nil(_)) => "nil",
+            AloxObject::Value(Value::Bool(_)) => "bool",
+            AloxObject::Value(Value::Error(_)) => "error",
+            AloxObject::Value(Value::List(_)) => "list",
+            AloxObject::Value(Value::Map(_)) => "map",
+            AloxObject::Value(Value::Range(_)) => "range",
+            AloxObject::Value(Value::Variant(_)) => "enum variant",
+            AloxObject::Value(Value::Instance(_)) => "instance",
+            AloxObject::Function(_) => "function",
+            AloxObject::Module(_) => "module",
+            AloxObject::Enum(_) => "enum",
+            AloxObject::Class(_) => "class",
+            AloxObject::Trait(_) => "trait",
+            AloxObject::Generator(_) => "generator",
+            AloxObject::Expr(_) => "expression",
         }
     }
 
@@ -78,9 +347,13 @@ impl AloxObject {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FunctionKind {
     Function,
     Method,
+    Getter,
+    Setter,
 }
 
 impl Display for FunctionKind {
@@ -88,6 +361,8 @@ impl Display for FunctionKind {
         match self {
             FunctionKind::Function => write!(f, "function"),
             FunctionKind::Method => write!(f, "method"),
+            FunctionKind::Getter => write!(f, "getter"),
+            FunctionKind::Setter => write!(f, "setter"),
         }
     }
 }
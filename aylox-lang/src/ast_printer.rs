@@ -1,4 +1,4 @@
-use crate::ast::*;
+use crate::{ast::*, token::TokenType};
 pub struct AstPrinter;
 impl AstPrinter {
     pub fn print(&mut self, expression: &Expr) -> String {
@@ -14,17 +14,53 @@ impl ExprVisitor<String> for AstPrinter {
         )
     }
 
+    fn visit_get(&mut self, get: &Get) -> String {
+        let access = if get.optional { "?." } else { "." };
+        parenthesize(
+            self,
+            &format!("{}{}", access, get.name.lexeme),
+            &[&get.object],
+        )
+    }
+
+    fn visit_optional_chain(&mut self, optional_chain: &OptionalChain) -> String {
+        self.visit_expr(&optional_chain.expression)
+    }
+
+    fn visit_set(&mut self, set: &Set) -> String {
+        parenthesize(
+            self,
+            &format!(".{}=", set.name.lexeme),
+            &[&set.object, &set.value],
+        )
+    }
+
+    fn visit_super_(&mut self, super_: &Super_) -> String {
+        format!("super.{}", super_.method.lexeme)
+    }
+
+    fn visit_this(&mut self, _this: &This) -> String {
+        "this".to_string()
+    }
+
+    fn visit_index(&mut self, index: &Index) -> String {
+        parenthesize(self, "[]", &[&index.object, &index.index])
+    }
+
+    fn visit_set_index(&mut self, set_index: &SetIndex) -> String {
+        parenthesize(
+            self,
+            "[]=",
+            &[&set_index.object, &set_index.index, &set_index.value],
+        )
+    }
+
     fn visit_grouping(&mut self, grouping: &Grouping) -> String {
         parenthesize(self, "group", &[&grouping.expression])
     }
 
     fn visit_literal(&mut self, literal: &Literal) -> String {
-        match &literal.value {
-            Value::String(val) => val.clone(),
-            Value::Number(val) => val.to_string(),
-            Value::Nil(_) => "Nil".to_owned(),
-            Value::Bool(val) => val.to_string(),
-        }
+        literal.value.to_string()
     }
 
     fn visit_unary(&mut self, unary: &Unary) -> String {
@@ -39,12 +75,101 @@ impl ExprVisitor<String> for AstPrinter {
         todo!()
     }
 
-    fn visit_logical(&mut self, _logical: &Logical) -> String {
-        todo!()
+    fn visit_logical(&mut self, logical: &Logical) -> String {
+        parenthesize(
+            self,
+            &logical.operator.lexeme,
+            &[&logical.left, &logical.right],
+        )
     }
 
-    fn visit_call(&mut self, _call: &Call) -> String {
-        todo!()
+    fn visit_call(&mut self, call: &Call) -> String {
+        // a call written as `x |> f(a)` has the pipe in place of its closing paren
+        if let (TokenType::PipeGreater, Some((piped, rest))) =
+            (&call.paren._type, call.arguments.split_first())
+        {
+            let stage = if rest.is_empty() {
+                self.visit_expr(&call.callee)
+            } else {
+                let mut stage = vec![&*call.callee];
+                stage.extend(rest);
+                parenthesize(self, "call", &stage)
+            };
+            return format!("(|> {} {})", self.visit_expr(piped), stage);
+        }
+        let mut expressions = vec![&*call.callee];
+        expressions.extend(&call.arguments);
+        let name = if call.optional { "?.call" } else { "call" };
+        parenthesize(self, name, &expressions)
+    }
+
+    fn visit_compound_assign(&mut self, compound_assign: &CompoundAssign) -> String {
+        parenthesize(
+            self,
+            &compound_assign.operator.lexeme,
+            &[&compound_assign.target, &compound_assign.value],
+        )
+    }
+
+    fn visit_increment(&mut self, increment: &Increment) -> String {
+        let target = self.visit_expr(&increment.target);
+        if increment.prefix {
+            format!("({} {})", increment.operator.lexeme, target)
+        } else {
+            format!("({} {})", target, increment.operator.lexeme)
+        }
+    }
+
+    fn visit_destructure_assign(&mut self, destructure_assign: &DestructureAssign) -> String {
+        format!(
+            "(= {} {})",
+            destructure_assign.pattern,
+            self.visit_expr(&destructure_assign.value)
+        )
+    }
+
+    fn visit_list(&mut self, list: &List) -> String {
+        let elements: Vec<&Expr> = list.elements.iter().collect();
+        parenthesize(self, "list", &elements)
+    }
+
+    fn visit_map(&mut self, map: &Map) -> String {
+        let entries: Vec<&Expr> = map
+            .keys
+            .iter()
+            .zip(map.values.iter())
+            .flat_map(|(key, value)| vec![key, value])
+            .collect();
+        parenthesize(self, "map", &entries)
+    }
+
+    fn visit_match_(&mut self, match_: &Match_) -> String {
+        let mut builder = format!("(match {}", self.visit_expr(&match_.subject));
+        for arm in match_.arms.iter() {
+            builder.push_str(&format!(
+                " ({} {})",
+                arm.pattern,
+                self.visit_expr(&arm.body)
+            ));
+        }
+        builder.push(')');
+        builder
+    }
+
+    fn visit_range(&mut self, range: &Range) -> String {
+        parenthesize(self, &range.operator.lexeme, &[&range.start, &range.end])
+    }
+
+    fn visit_conditional(&mut self, conditional: &Conditional) -> String {
+        parenthesize(
+            self,
+            "?:",
+            &[
+                &conditional.condition,
+                &conditional.then_branch,
+                &conditional.else_branch,
+            ],
+        )
     }
 }
 
@@ -60,3 +185,38 @@ fn parenthesize(visitor: &mut AstPrinter, operator: &str, expressions: Expressio
     builder.push(')');
     builder
 }
+
+#[cfg(test)]
+mod tests {
+    use super::AstPrinter;
+    use crate::{ast::Stmt, parser::Parser, scanner::Scanner};
+
+    fn print(source: &str) -> String {
+        let mut scanner = Scanner::new(source);
+        let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+        match parser
+            .parse()
+            .expect("test expression should parse")
+            .as_slice()
+        {
+            [Stmt::Expression(statement)] => AstPrinter.print(&statement.expression),
+            other => panic!("expected one expression statement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pipelines_print_as_written() {
+        assert_eq!(print("x |> f;"), "(|> var x var f)");
+        assert_eq!(
+            print("x |> a |> b(2);"),
+            "(|> (|> var x var a) (call var b 2))"
+        );
+        assert_eq!(print("b(x, 2);"), "(call var b var x 2)");
+    }
+
+    #[test]
+    fn optional_access_and_coalescing() {
+        assert_eq!(print("a?.b ?? c;"), "(?? (?.b var a) var c)");
+        assert_eq!(print("f?.(1);"), "(?.call var f 1)");
+    }
+}
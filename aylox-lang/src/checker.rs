@@ -0,0 +1,829 @@
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::{
+    ast::*,
+    error::TypeError,
+    token::{Token, TokenType},
+};
+
+type CheckResult = Result<(), TypeError>;
+type TypeResult = Result<Type, TypeError>;
+
+/// The static type of an expression. Unannotated variables and anything the checker
+/// cannot follow, like properties or native functions, are `Dynamic` and match every type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Dynamic,
+    Nil,
+    Bool,
+    Integer,
+    Float,
+    /// Either an integer or a float.
+    Number,
+    String,
+    List,
+    Map,
+    Range,
+    Error,
+    /// A function, with its signature when it was declared with `fun`.
+    Function(Option<Rc<Signature>>),
+}
+
+/// The annotated parameter and return types of a function declaration.
+#[derive(Debug, PartialEq)]
+pub struct Signature {
+    name: String,
+    params: Vec<Type>,
+    returns: Type,
+}
+
+impl Type {
+    /// The type named by an annotation such as `: Number`.
+    fn from_annotation(name: &Token) -> TypeResult {
+        Ok(match name.lexeme.as_str() {
+            "Any" => Type::Dynamic,
+            "Nil" => Type::Nil,
+            "Bool" => Type::Bool,
+            "Integer" => Type::Integer,
+            "Float" => Type::Float,
+            "Number" => Type::Number,
+            "String" => Type::String,
+            "List" => Type::List,
+            "Map" => Type::Map,
+            "Range" => Type::Range,
+            "Error" => Type::Error,
+            "Function" => Type::Function(None),
+            _ => {
+                return Err(TypeError::UnknownType {
+                    lexeme: name.lexeme.clone(),
+                    line: name.line,
+                })
+            }
+        })
+    }
+
+    fn of_value(value: &Value) -> Type {
+        match value {
+            Value::String(_) => Type::String,
+            Value::Number(_) => Type::Float,
+            Value::Integer(_) => Type::Integer,
+            Value::Nil(_) => Type::Nil,
+            Value::Bool(_) => Type::Bool,
+            Value::Error(_) => Type::Error,
+            Value::List(_) => Type::List,
+            Value::Map(_) => Type::Map,
+            Value::Range(_) => Type::Range,
+            Value::Variant(_) | Value::Instance(_) => Type::Dynamic,
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(self, Type::Integer | Type::Float | Type::Number)
+    }
+
+    /// Whether a value of type `found` may be stored where `self` is expected.
+    fn accepts(&self, found: &Type) -> bool {
+        match (self, found) {
+            (Type::Dynamic, _) | (_, Type::Dynamic) => true,
+            (Type::Number, found) => found.is_numeric(),
+            (Type::Function(_), Type::Function(_)) => true,
+            (expected, found) => expected == found,
+        }
+    }
+
+    /// The common type of two branches, or `Dynamic` if they differ.
+    fn join(self, other: Type) -> Type {
+        if self == other {
+            self
+        } else if self.is_numeric() && other.is_numeric() {
+            Type::Number
+        } else {
+            Type::Dynamic
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Type::Dynamic => "Any",
+            Type::Nil => "Nil",
+            Type::Bool => "Bool",
+            Type::Integer => "Integer",
+            Type::Float => "Float",
+            Type::Number => "Number",
+            Type::String => "String",
+            Type::List => "List",
+            Type::Map => "Map",
+            Type::Range => "Range",
+            Type::Error => "Error",
+            Type::Function(_) => "Function",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone)]
+struct Binding {
+    /// What assignments to the variable must match, from its annotation.
+    declared: Type,
+    /// What reading the variable gives.
+    current: Type,
+}
+
+/// Checks type annotations before the program runs, reporting operators applied to
+/// the wrong types and values that don't match an annotated variable, parameter or
+/// return type. Unannotated code is dynamic and is left to the interpreter.
+pub struct Checker {
+    scopes: Vec<HashMap<String, Binding>>,
+    /// The annotated return type of each enclosing function.
+    returns: Vec<Type>,
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            returns: Vec::new(),
+        }
+    }
+
+    pub fn check(&mut self, statements: &[Stmt]) -> CheckResult {
+        self.check_stmts(statements)
+    }
+
+    fn check_stmts(&mut self, statements: &[Stmt]) -> CheckResult {
+        for statement in statements.iter() {
+            self.visit_stmt(statement)?;
+        }
+        Ok(())
+    }
+
+    fn check_block(&mut self, statements: &[Stmt]) -> CheckResult {
+        self.scopes.push(HashMap::new());
+        let result = self.check_stmts(statements);
+        self.scopes.pop();
+        result
+    }
+
+    fn annotated(&self, annotation: &Option<Token>) -> TypeResult {
+        match annotation {
+            Some(name) => Type::from_annotation(name),
+            None => Ok(Type::Dynamic),
+        }
+    }
+
+    fn define(&mut self, name: &Token, declared: Type, current: Type) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), Binding { declared, current });
+        }
+    }
+
+    fn define_dynamic(&mut self, name: &Token) {
+        self.define(name, Type::Dynamic, Type::Dynamic);
+    }
+
+    fn lookup(&mut self, name: &Token) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(&name.lexeme))
+    }
+
+    /// Checks a value stored in `name`, which then holds a value of the declared type.
+    fn assign(&mut self, name: &Token, found: Type, line: usize) -> CheckResult {
+        if let Some(binding) = self.lookup(name) {
+            expect(
+                &binding.declared,
+                &found,
+                || format!("'{}'", name.lexeme),
+                line,
+            )?;
+            binding.current = binding.declared.clone();
+        }
+        Ok(())
+    }
+
+    fn check_function(&mut self, function: &Function, signature: &Signature) -> CheckResult {
+        self.scopes.push(HashMap::new());
+        let required = function.params.len() - function.defaults.len();
+        for (i, (param, param_type)) in function.params.iter().zip(&signature.params).enumerate() {
+            if let Some(default) = i.checked_sub(required) {
+                let found = self.visit_expr(&function.defaults[default])?;
+                let context = || format!("the default of '{}'", param.lexeme);
+                expect(param_type, &found, context, param.line)?;
+            }
+            self.define(param, param_type.clone(), param_type.clone());
+        }
+        if let Some(rest) = &function.rest {
+            self.define(rest, Type::List, Type::List);
+        }
+        self.returns.push(signature.returns.clone());
+        let result = self.check_stmts(&function.body);
+        self.returns.pop();
+        self.scopes.pop();
+        result
+    }
+
+    /// Checks each arm with the names its pattern binds in scope, returning the arm types.
+    fn check_arms<T, R>(
+        &mut self,
+        subject: &Expr,
+        arms: &[Arm<T>],
+        check_body: impl Fn(&mut Self, &T) -> Result<R, TypeError>,
+    ) -> Result<Vec<R>, TypeError> {
+        self.visit_expr(subject)?;
+        let mut results = Vec::with_capacity(arms.len());
+        for arm in arms.iter() {
+            self.scopes.push(HashMap::new());
+            self.define_bindings(&arm.pattern);
+            let result = check_body(self, &arm.body);
+            self.scopes.pop();
+            results.push(result?);
+        }
+        Ok(results)
+    }
+
+    fn define_bindings(&mut self, pattern: &MatchPattern) {
+        match pattern {
+            MatchPattern::Identifier(name) => self.define_dynamic(name),
+            MatchPattern::Variant { fields, .. } => {
+                for field in fields.iter() {
+                    self.define_bindings(field);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn signature(&self, function: &Function) -> Result<Signature, TypeError> {
+        let params = function
+            .param_types
+            .iter()
+            .map(|annotation| self.annotated(annotation))
+            .collect::<Result<_, _>>()?;
+        let returns = if function.generator {
+            Type::Dynamic
+        } else {
+            self.annotated(&function.return_type)?
+        };
+        Ok(Signature {
+            name: function.name.lexeme.clone(),
+            params,
+            returns,
+        })
+    }
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn expect(
+    expected: &Type,
+    found: &Type,
+    context: impl FnOnce() -> String,
+    line: usize,
+) -> CheckResult {
+    if expected.accepts(found) {
+        Ok(())
+    } else {
+        Err(TypeError::Mismatch {
+            expected: expected.to_string(),
+            found: found.to_string(),
+            context: context(),
+            line,
+        })
+    }
+}
+
+/// The type of `left operator right`, mirroring the operands `binary_operation` accepts.
+fn binary_type(operator: &Token, left: Type, right: Type) -> TypeResult {
+    let invalid = || TypeError::InvalidOperands {
+        lexeme: operator.lexeme.clone(),
+        left: left.to_string(),
+        right: right.to_string(),
+        line: operator.line,
+    };
+    match operator._type {
+        TokenType::EqualEqual | TokenType::BangEqual => Ok(Type::Bool),
+        // a dynamic operand may be an instance overloading the operator for any other operand
+        _ if left == Type::Dynamic || right == Type::Dynamic => Ok(Type::Dynamic),
+        TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => {
+            if left.is_numeric() && right.is_numeric() {
+                Ok(Type::Bool)
+            } else {
+                Err(invalid())
+            }
+        }
+        TokenType::Plus => match (&left, &right) {
+            (Type::String, other) | (other, Type::String) if other.is_numeric() => Ok(Type::String),
+            (Type::String, Type::String) => Ok(Type::String),
+            (left, right) if left.is_numeric() && right.is_numeric() => {
+                Ok(arithmetic_type(left, right))
+            }
+            _ => Err(invalid()),
+        },
+        _ if !left.is_numeric() || !right.is_numeric() => Err(invalid()),
+        TokenType::Slash => Ok(Type::Float),
+        TokenType::StarStar => Ok(match (&left, &right) {
+            (Type::Float, _) | (_, Type::Float) => Type::Float,
+            _ => Type::Number,
+        }),
+        _ => Ok(arithmetic_type(&left, &right)),
+    }
+}
+
+/// Integers stay integers, and mixing in a float makes a float.
+fn arithmetic_type(left: &Type, right: &Type) -> Type {
+    match (left, right) {
+        (Type::Integer, Type::Integer) => Type::Integer,
+        (Type::Float, _) | (_, Type::Float) => Type::Float,
+        _ => Type::Number,
+    }
+}
+
+impl StmtVisitor<CheckResult> for Checker {
+    fn visit_expression(&mut self, expression: &Expression) -> CheckResult {
+        self.visit_expr(&expression.expression)?;
+        Ok(())
+    }
+
+    fn visit_function(&mut self, function: &Function) -> CheckResult {
+        let signature = Rc::new(self.signature(function)?);
+        for decorator in function.decorators.iter() {
+            self.visit_expr(decorator)?;
+        }
+        // a decorator can replace the function with anything
+        let function_type = if function.decorators.is_empty() {
+            Type::Function(Some(signature.clone()))
+        } else {
+            Type::Dynamic
+        };
+        self.define(&function.name, Type::Dynamic, function_type);
+        self.check_function(function, &signature)
+    }
+
+    fn visit_if_(&mut self, if_: &If_) -> CheckResult {
+        self.visit_expr(&if_.condition)?;
+        self.visit_stmt(&if_.then_branch)?;
+        if let Some(else_branch) = &if_.else_branch {
+            self.visit_stmt(else_branch)?;
+        }
+        Ok(())
+    }
+
+    fn visit_print(&mut self, print: &Print) -> CheckResult {
+        self.visit_expr(&print.expression)?;
+        Ok(())
+    }
+
+    fn visit_while_(&mut self, while_: &While_) -> CheckResult {
+        self.visit_expr(&while_.condition)?;
+        self.visit_stmt(&while_.body)
+    }
+
+    fn visit_for_in(&mut self, for_in: &ForIn) -> CheckResult {
+        let element = match self.visit_expr(&for_in.iterable)? {
+            Type::Range => Type::Integer,
+            Type::String => Type::String,
+            _ => Type::Dynamic,
+        };
+        self.scopes.push(HashMap::new());
+        self.define(&for_in.name, Type::Dynamic, element);
+        let result = self.visit_stmt(&for_in.body);
+        self.scopes.pop();
+        result
+    }
+
+    fn visit_return_(&mut self, return_: &Return_) -> CheckResult {
+        let found = match &return_.value {
+            Some(value) => self.visit_expr(value)?,
+            None => Type::Nil,
+        };
+        if let Some(expected) = self.returns.last() {
+            expect(
+                expected,
+                &found,
+                || "the return value".to_string(),
+                return_.keyword.line,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn visit_yield_(&mut self, yield_: &Yield_) -> CheckResult {
+        if let Some(value) = &yield_.value {
+            self.visit_expr(value)?;
+        }
+        Ok(())
+    }
+
+    fn visit_defer(&mut self, defer: &Defer) -> CheckResult {
+        self.visit_expr(&defer.expression)?;
+        Ok(())
+    }
+
+    fn visit_throw(&mut self, throw: &Throw) -> CheckResult {
+        self.visit_expr(&throw.value)?;
+        Ok(())
+    }
+
+    fn visit_try_(&mut self, try_: &Try_) -> CheckResult {
+        self.check_block(&try_.body)?;
+        if let Some(catch_name) = &try_.catch_name {
+            self.scopes.push(HashMap::new());
+            self.define_dynamic(catch_name);
+            let result = self.check_stmts(&try_.catch_body);
+            self.scopes.pop();
+            result?;
+        }
+        self.check_block(&try_.finally_body)
+    }
+
+    fn visit_import(&mut self, import: &Import) -> CheckResult {
+        for name in import.alias.iter().chain(import.names.iter()) {
+            self.define_dynamic(name);
+        }
+        Ok(())
+    }
+
+    fn visit_var(&mut self, var: &Var) -> CheckResult {
+        let declared = self.annotated(&var.annotation)?;
+        if let Some(initializer) = &var.initializer {
+            let found = self.visit_expr(initializer)?;
+            let context = || format!("'{}'", var.name.lexeme);
+            expect(&declared, &found, context, var.name.line)?;
+        }
+        self.define(&var.name, declared.clone(), declared);
+        Ok(())
+    }
+
+    fn visit_destructure(&mut self, destructure: &Destructure) -> CheckResult {
+        self.visit_expr(&destructure.initializer)?;
+        for name in destructure.pattern.names() {
+            self.define_dynamic(name);
+        }
+        Ok(())
+    }
+
+    fn visit_class(&mut self, class: &Class) -> CheckResult {
+        self.define_dynamic(&class.name);
+        for expr in class.superclass.iter().chain(class.traits.iter()) {
+            self.visit_expr(expr)?;
+        }
+        let members = class
+            .methods
+            .iter()
+            .chain(class.class_methods.iter())
+            .chain(class.getters.iter())
+            .chain(class.setters.iter());
+        for method in members {
+            let signature = self.signature(method)?;
+            self.check_function(method, &signature)?;
+        }
+        Ok(())
+    }
+
+    fn visit_trait_(&mut self, trait_: &Trait_) -> CheckResult {
+        self.define_dynamic(&trait_.name);
+        for method in trait_.methods.iter() {
+            let signature = self.signature(method)?;
+            self.check_function(method, &signature)?;
+        }
+        Ok(())
+    }
+
+    fn visit_enum_(&mut self, enum_: &Enum_) -> CheckResult {
+        self.define_dynamic(&enum_.name);
+        for variant in enum_.variants.iter() {
+            self.define_dynamic(&variant.name);
+        }
+        Ok(())
+    }
+
+    fn visit_match_stmt(&mut self, match_stmt: &MatchStmt) -> CheckResult {
+        self.check_arms(&match_stmt.subject, &match_stmt.arms, Self::visit_stmt)?;
+        Ok(())
+    }
+
+    fn visit_switch(&mut self, switch: &Switch) -> CheckResult {
+        self.visit_expr(&switch.subject)?;
+        for case in switch.cases.iter() {
+            for value in case.values.iter() {
+                self.visit_expr(value)?;
+            }
+            self.visit_stmt(&case.body)?;
+        }
+        if let Some(default_branch) = &switch.default_branch {
+            self.visit_stmt(default_branch)?;
+        }
+        Ok(())
+    }
+
+    fn visit_block(&mut self, block: &Block) -> CheckResult {
+        self.check_block(&block.statements)
+    }
+}
+
+impl ExprVisitor<TypeResult> for Checker {
+    fn visit_assign(&mut self, assign: &Assign) -> TypeResult {
+        let found = self.visit_expr(&assign.value)?;
+        self.assign(&assign.name, found.clone(), assign.name.line)?;
+        Ok(found)
+    }
+
+    fn visit_destructure_assign(&mut self, destructure_assign: &DestructureAssign) -> TypeResult {
+        let found = self.visit_expr(&destructure_assign.value)?;
+        for name in destructure_assign.pattern.names() {
+            self.assign(name, Type::Dynamic, name.line)?;
+        }
+        Ok(found)
+    }
+
+    fn visit_binary(&mut self, binary: &Binary) -> TypeResult {
+        let left = self.visit_expr(&binary.left)?;
+        let right = self.visit_expr(&binary.right)?;
+        binary_type(&binary.operator, left, right)
+    }
+
+    fn visit_call(&mut self, call: &Call) -> TypeResult {
+        let callee = self.visit_expr(&call.callee)?;
+        let mut arguments = Vec::with_capacity(call.arguments.len());
+        for argument in call.arguments.iter() {
+            arguments.push(self.visit_expr(argument)?);
+        }
+        let signature = match callee {
+            Type::Function(Some(signature)) => signature,
+            _ => return Ok(Type::Dynamic),
+        };
+        // missing or extra arguments are reported when the call runs
+        for (i, (expected, found)) in signature.params.iter().zip(&arguments).enumerate() {
+            let context = || format!("argument {} of '{}'", i + 1, signature.name);
+            expect(expected, found, context, call.paren.line)?;
+        }
+        Ok(signature.returns.clone())
+    }
+
+    fn visit_compound_assign(&mut self, compound_assign: &CompoundAssign) -> TypeResult {
+        let operator = &compound_assign.operator;
+        let arithmetic = Token::new(
+            match operator._type {
+                TokenType::PlusEqual => TokenType::Plus,
+                TokenType::MinusEqual => TokenType::Minus,
+                TokenType::StarEqual => TokenType::Star,
+                _ => TokenType::Slash,
+            },
+            &operator.lexeme,
+            operator.line,
+        );
+        let current = self.visit_expr(&compound_assign.target)?;
+        let value = self.visit_expr(&compound_assign.value)?;
+        let updated = binary_type(&arithmetic, current, value)?;
+        if let Expr::Variable(variable) = &*compound_assign.target {
+            self.assign(&variable.name, updated.clone(), operator.line)?;
+        }
+        Ok(updated)
+    }
+
+    fn visit_increment(&mut self, increment: &Increment) -> TypeResult {
+        let current = self.visit_expr(&increment.target)?;
+        if current.is_numeric() || current == Type::Dynamic {
+            Ok(current)
+        } else {
+            Err(TypeError::InvalidOperand {
+                lexeme: increment.operator.lexeme.clone(),
+                operand: current.to_string(),
+                line: increment.operator.line,
+            })
+        }
+    }
+
+    fn visit_conditional(&mut self, conditional: &Conditional) -> TypeResult {
+        self.visit_expr(&conditional.condition)?;
+        let then_branch = self.visit_expr(&conditional.then_branch)?;
+        let else_branch = self.visit_expr(&conditional.else_branch)?;
+        Ok(then_branch.join(else_branch))
+    }
+
+    fn visit_get(&mut self, get: &Get) -> TypeResult {
+        self.visit_expr(&get.object)?;
+        Ok(Type::Dynamic)
+    }
+
+    fn visit_set(&mut self, set: &Set) -> TypeResult {
+        self.visit_expr(&set.object)?;
+        self.visit_expr(&set.value)
+    }
+
+    fn visit_super_(&mut self, _super: &Super_) -> TypeResult {
+        Ok(Type::Dynamic)
+    }
+
+    fn visit_this(&mut self, _this: &This) -> TypeResult {
+        Ok(Type::Dynamic)
+    }
+
+    fn visit_index(&mut self, index: &Index) -> TypeResult {
+        self.visit_expr(&index.object)?;
+        self.visit_expr(&index.index)?;
+        Ok(Type::Dynamic)
+    }
+
+    fn visit_set_index(&mut self, set_index: &SetIndex) -> TypeResult {
+        self.visit_expr(&set_index.object)?;
+        self.visit_expr(&set_index.index)?;
+        self.visit_expr(&set_index.value)
+    }
+
+    fn visit_optional_chain(&mut self, optional_chain: &OptionalChain) -> TypeResult {
+        // the chain may short-circuit to nil, so its type is only known at runtime
+        self.visit_expr(&optional_chain.expression)?;
+        Ok(Type::Dynamic)
+    }
+
+    fn visit_grouping(&mut self, grouping: &Grouping) -> TypeResult {
+        self.visit_expr(&grouping.expression)
+    }
+
+    fn visit_list(&mut self, list: &List) -> TypeResult {
+        for element in list.elements.iter() {
+            self.visit_expr(element)?;
+        }
+        Ok(Type::List)
+    }
+
+    fn visit_literal(&mut self, literal: &Literal) -> TypeResult {
+        Ok(Type::of_value(&literal.value))
+    }
+
+    fn visit_map(&mut self, map: &Map) -> TypeResult {
+        for (key, value) in map.keys.iter().zip(map.values.iter()) {
+            self.visit_expr(key)?;
+            self.visit_expr(value)?;
+        }
+        Ok(Type::Map)
+    }
+
+    fn visit_match_(&mut self, match_: &Match_) -> TypeResult {
+        let types = self.check_arms(&match_.subject, &match_.arms, Self::visit_expr)?;
+        Ok(types
+            .into_iter()
+            .reduce(Type::join)
+            .unwrap_or(Type::Dynamic))
+    }
+
+    fn visit_range(&mut self, range: &Range) -> TypeResult {
+        let start = self.visit_expr(&range.start)?;
+        let end = self.visit_expr(&range.end)?;
+        // a `Number` may hold an integer, so only floats and non-numbers are rejected
+        let bound = |ty: &Type| matches!(ty, Type::Integer | Type::Number | Type::Dynamic);
+        if bound(&start) && bound(&end) {
+            Ok(Type::Range)
+        } else {
+            Err(TypeError::InvalidOperands {
+                lexeme: range.operator.lexeme.clone(),
+                left: start.to_string(),
+                right: end.to_string(),
+                line: range.operator.line,
+            })
+        }
+    }
+
+    fn visit_logical(&mut self, logical: &Logical) -> TypeResult {
+        let left = self.visit_expr(&logical.left)?;
+        let right = self.visit_expr(&logical.right)?;
+        if logical.operator._type == TokenType::QuestionQuestion && left == Type::Nil {
+            return Ok(right);
+        }
+        Ok(left.join(right))
+    }
+
+    fn visit_unary(&mut self, unary: &Unary) -> TypeResult {
+        let right = self.visit_expr(&unary.right)?;
+        match unary.operator._type {
+            TokenType::Bang => Ok(Type::Bool),
+            _ if right.is_numeric() || right == Type::Dynamic => Ok(right),
+            _ => Err(TypeError::InvalidOperand {
+                lexeme: unary.operator.lexeme.clone(),
+                operand: right.to_string(),
+                line: unary.operator.line,
+            }),
+        }
+    }
+
+    fn visit_variable(&mut self, variable: &Variable) -> TypeResult {
+        Ok(self
+            .lookup(&variable.name)
+            .map_or(Type::Dynamic, |binding| binding.current.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Checker;
+    use crate::{error::TypeError, parser::Parser, scanner::Scanner};
+
+    fn check(source: &str) -> Result<(), TypeError> {
+        let mut scanner = Scanner::new(source);
+        let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+        let statements = parser.parse().expect("test program should parse");
+        Checker::new().check(&statements)
+    }
+
+    #[test]
+    fn unannotated_code_is_dynamic() {
+        for source in &[
+            "var a = true; var b = a + 1;",
+            "fun add(a, b) { return a + b; } add(true, nil);",
+            "var s = \"a\"; s = 1; s -= 1;",
+        ] {
+            assert!(check(source).is_ok(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn annotated_code_is_accepted() {
+        for source in &[
+            "var x: Number = 1; x = 2.5; x += 1;",
+            "var s: String = \"n = \" + 1;",
+            "fun add(a: Number, b: Number = 2): Number { return a + b; } var c: Number = add(1);",
+            "fun half(n: Integer): Float { return n / 2; }",
+            "var r: Range = 0..3; for (i in r) { var j: Integer = i * 2; }",
+            "fun f(g: Function, ...rest) { var l: List = rest; } f(f);",
+        ] {
+            assert!(check(source).is_ok(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn operators_on_wrong_types_are_rejected() {
+        let err = check("var b: Bool = true; var n: Number = 1; print b + n;").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "[line 1] '+' cannot be applied to Bool and Number."
+        );
+        for source in &[
+            "print true - 1;",
+            "print \"a\" < 1;",
+            "print -\"a\";",
+            "var b: Bool = false; b++;",
+            "print 0.5..2;",
+        ] {
+            assert!(
+                matches!(
+                    check(source),
+                    Err(TypeError::InvalidOperands { .. }) | Err(TypeError::InvalidOperand { .. })
+                ),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn mismatched_annotations_are_rejected() {
+        let err = check("fun add(a: Number, b: Number) {}\nadd(1, \"two\");").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "[line 2] Expected Number for argument 2 of 'add' but found String."
+        );
+        for source in &[
+            "var x: Number = \"one\";",
+            "var x: Integer = 1; x = 1.5;",
+            "var x: Integer = 1; x /= 2;",
+            "fun f(): String { return 1; }",
+            "fun f(): Number { return; }",
+            "fun f(a: Bool = 1) {}",
+        ] {
+            assert!(
+                matches!(check(source), Err(TypeError::Mismatch { .. })),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_types_are_rejected() {
+        assert!(matches!(
+            check("var x: Numbr = 1;"),
+            Err(TypeError::UnknownType { .. })
+        ));
+    }
+
+    #[test]
+    fn dynamic_operands_may_be_overloaded() {
+        let source = r#"class Bag {
+                init() { this.size = 0; }
+                __add(item) { this.size += 1; return this; }
+                __lt(other) { return true; }
+            }
+            var bag = Bag();
+            bag = bag + true + "x" + nil;
+            var smaller = bag < "a";"#;
+        assert!(check(source).is_ok());
+    }
+}
@@ -0,0 +1,201 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Display, ptr, rc::Rc};
+
+use crate::{
+    ast::*,
+    error::RuntimeException,
+    functions::{AloxFunction, Callable},
+    interpreter::Interpreter,
+};
+
+pub type Methods = HashMap<String, AloxFunction>;
+
+/// A `class` declaration with its methods, which are bound to an instance when read from it.
+/// Getters and setters run when a property of that name is read or assigned, while
+/// class methods are read from the class itself. Members not declared on the class are
+/// looked up on its superclass.
+#[derive(Debug)]
+pub struct AloxClass {
+    pub name: String,
+    pub superclass: Option<Rc<AloxClass>>,
+    methods: Methods,
+    class_methods: Methods,
+    getters: Methods,
+    setters: Methods,
+}
+
+impl AloxClass {
+    pub fn new(
+        name: String,
+        superclass: Option<Rc<AloxClass>>,
+        methods: Methods,
+        class_methods: Methods,
+        getters: Methods,
+        setters: Methods,
+    ) -> Self {
+        Self {
+            name,
+            superclass,
+            methods,
+            class_methods,
+            getters,
+            setters,
+        }
+    }
+
+    pub fn find_method(&self, name: &str) -> Option<&AloxFunction> {
+        self.find(name, |class| &class.methods)
+    }
+
+    pub fn find_class_method(&self, name: &str) -> Option<&AloxFunction> {
+        self.find(name, |class| &class.class_methods)
+    }
+
+    pub fn find_getter(&self, name: &str) -> Option<&AloxFunction> {
+        self.find(name, |class| &class.getters)
+    }
+
+    pub fn find_setter(&self, name: &str) -> Option<&AloxFunction> {
+        self.find(name, |class| &class.setters)
+    }
+
+    fn find(&self, name: &str, members: fn(&AloxClass) -> &Methods) -> Option<&AloxFunction> {
+        members(self).get(name).or_else(|| {
+            self.superclass
+                .as_ref()
+                .and_then(|superclass| superclass.find(name, members))
+        })
+    }
+}
+
+/// A `trait` declaration, whose methods are copied into each class declared `with` it.
+#[derive(Debug)]
+pub struct AloxTrait {
+    pub name: String,
+    pub methods: Methods,
+}
+
+impl Display for AloxTrait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<trait {}>", self.name)
+    }
+}
+
+/// Adds the methods of `traits` to the `methods` of the class `class_name`. The class's
+/// own methods win over those of its traits, but two traits providing a method the
+/// class does not declare is an error.
+pub fn mix_in(
+    class_name: &str,
+    methods: &mut Methods,
+    traits: &[Rc<AloxTrait>],
+    line: usize,
+) -> Result<(), RuntimeException> {
+    let mut origins: HashMap<&str, &str> = HashMap::new();
+    for mixin in traits.iter() {
+        for (name, method) in mixin.methods.iter() {
+            if methods.contains_key(name) && !origins.contains_key(name.as_str()) {
+                continue;
+            }
+            if let Some(first) = origins.insert(name, &mixin.name) {
+                return Err(RuntimeException::TraitConflict {
+                    class: class_name.to_string(),
+                    method: name.clone(),
+                    first: first.to_string(),
+                    second: mixin.name.clone(),
+                    line,
+                });
+            }
+            methods.insert(name.clone(), method.clone());
+        }
+    }
+    Ok(())
+}
+
+impl Display for AloxClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<class {}>", self.name)
+    }
+}
+
+/// An object made by calling a class, holding the fields assigned to it.
+pub struct AloxInstance {
+    pub class: Rc<AloxClass>,
+    fields: RefCell<HashMap<String, AloxObject>>,
+}
+
+impl AloxInstance {
+    pub fn new(class: Rc<AloxClass>) -> Self {
+        Self {
+            class,
+            fields: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<AloxObject> {
+        self.fields.borrow().get(name).cloned()
+    }
+
+    pub fn set_field(&self, name: &str, value: AloxObject) {
+        self.fields.borrow_mut().insert(name.to_string(), value);
+    }
+}
+
+impl PartialEq for AloxInstance {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+// fields may refer back to the instance, so only the class is shown
+impl std::fmt::Debug for AloxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Display for AloxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{} instance>", self.class.name)
+    }
+}
+
+/// Calling a class makes a new instance and runs its `init` method, if any, with the arguments.
+pub struct Constructor(pub Rc<AloxClass>);
+
+impl Constructor {
+    fn init(&self) -> Option<&AloxFunction> {
+        self.0.find_method("init")
+    }
+}
+
+impl Callable for Constructor {
+    fn needs_mut(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    fn arity(&self) -> usize {
+        self.init().map_or(0, Callable::arity)
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        self.init().map_or(Some(0), Callable::max_arity)
+    }
+
+    fn call(&self, _interpreter: &Interpreter, _args: &[AloxObject]) -> AloxObjResult {
+        unreachable!("class constructors are called through call_mut")
+    }
+
+    fn call_mut(&self, interpreter: &mut Interpreter, args: &[AloxObject]) -> AloxObjResult {
+        let instance = Rc::new(AloxInstance::new(self.0.clone()));
+        if let Some(init) = self.init() {
+            // the arguments were already checked against the arity of `init`
+            let init = Rc::new(init.bind(instance.clone()));
+            let line = interpreter.call_line;
+            interpreter.call_function(init, args, line)?;
+        }
+        Ok(AloxObject::Value(Value::Instance(instance)))
+    }
+}
@@ -0,0 +1,89 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::{ast::*, functions::Callable, interpreter::Interpreter};
+
+/// An `enum` declaration, holding what each of its variants evaluates to:
+/// a value for variants without fields and a constructor function for the rest.
+#[derive(Debug)]
+pub struct EnumType {
+    pub name: String,
+    pub variants: Vec<(String, AloxObject)>,
+}
+
+impl EnumType {
+    pub fn new(declaration: &Enum_) -> Self {
+        let name = declaration.name.lexeme.clone();
+        let variants = declaration
+            .variants
+            .iter()
+            .map(|variant| {
+                let object = if variant.fields.is_empty() {
+                    AloxObject::Value(Value::Variant(Rc::new(EnumValue {
+                        enum_name: name.clone(),
+                        variant: variant.name.lexeme.clone(),
+                        fields: vec![],
+                    })))
+                } else {
+                    AloxObject::Function(Rc::new(VariantConstructor {
+                        enum_name: name.clone(),
+                        variant: variant.name.lexeme.clone(),
+                        arity: variant.fields.len(),
+                    }))
+                };
+                (variant.name.lexeme.clone(), object)
+            })
+            .collect();
+        Self { name, variants }
+    }
+
+    pub fn variant(&self, name: &str) -> Option<&AloxObject> {
+        self.variants
+            .iter()
+            .find(|(variant, _)| variant == name)
+            .map(|(_, object)| object)
+    }
+}
+
+impl Display for EnumType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<enum {}>", self.name)
+    }
+}
+
+/// Calling a variant with fields, like `Circle(2)`, builds a value of that variant.
+#[derive(Debug)]
+pub struct VariantConstructor {
+    pub enum_name: String,
+    pub variant: String,
+    arity: usize,
+}
+
+impl Callable for VariantConstructor {
+    fn needs_mut(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        &self.variant
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(&self, _interpreter: &Interpreter, args: &[AloxObject]) -> AloxObjResult {
+        let fields = args
+            .iter()
+            .map(|arg| arg.clone().to_value())
+            .collect::<Result<_, _>>()?;
+        Ok(AloxObject::Value(Value::Variant(Rc::new(EnumValue {
+            enum_name: self.enum_name.clone(),
+            variant: self.variant.clone(),
+            fields,
+        }))))
+    }
+
+    fn call_mut(&self, _interpreter: &mut Interpreter, _args: &[AloxObject]) -> AloxObjResult {
+        unreachable!("enum variant constructors are called through call")
+    }
+}
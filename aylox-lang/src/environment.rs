@@ -1,10 +1,15 @@
 use crate::{ast::AloxObject, error::RuntimeException, functions::Callable, token::Token};
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
 #[derive(Debug, Clone)]
 pub struct Environment {
     enclosing: Option<Rc<RefCell<Environment>>>,
     values: HashMap<String, EnvValue>,
+    constants: HashSet<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -19,6 +24,7 @@ impl Environment {
         Self {
             values: HashMap::new(),
             enclosing: None,
+            constants: HashSet::new(),
         }
     }
 
@@ -26,6 +32,7 @@ impl Environment {
         Self {
             values: HashMap::new(),
             enclosing: Some(enclosing),
+            constants: HashSet::new(),
         }
     }
 
@@ -33,6 +40,24 @@ impl Environment {
         self.insert_env_value(name, value);
     }
 
+    /// Fails if `name` is a constant of this environment, which a declaration must not replace.
+    pub fn check_redeclaration(&self, name: &Token) -> Result<(), RuntimeException> {
+        if self.constants.contains(&name.lexeme) {
+            Err(RuntimeException::RedeclareConstant {
+                lexeme: name.lexeme.clone(),
+                line: name.line,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Defines a binding that `assign` refuses to change.
+    pub fn define_constant(&mut self, name: &str, value: AloxObject) {
+        self.constants.insert(name.to_string());
+        self.insert_env_value(name, Some(value));
+    }
+
     fn insert_env_value(&mut self, name: &str, value: Option<AloxObject>) {
         if let Some(value) = value {
             if let AloxObject::Function(func) = value {
@@ -52,7 +77,12 @@ impl Environment {
         name: &Token,
         value: Option<AloxObject>,
     ) -> Result<(), RuntimeException> {
-        if self.values.contains_key(&name.lexeme) {
+        if self.constants.contains(&name.lexeme) {
+            Err(RuntimeException::AssignToConstant {
+                lexeme: name.lexeme.clone(),
+                line: name.line,
+            })
+        } else if self.values.contains_key(&name.lexeme) {
             self.insert_env_value(&name.lexeme, value);
             Ok(())
         } else {
@@ -99,6 +129,17 @@ impl Environment {
         }
     }
 
+    /// The function bound to `name`, if there is one, looked up without raising errors.
+    pub fn function(&self, name: &str) -> Option<Rc<dyn Callable>> {
+        match self.values.get(name) {
+            Some(value) => match Environment::env_value_to_obj(value.clone()).as_ref() {
+                Some(AloxObject::Function(function)) => Some(function.clone()),
+                _ => None,
+            },
+            None => self.enclosing.as_ref()?.borrow().function(name),
+        }
+    }
+
     fn env_value_to_obj(val: EnvValue) -> Rc<Option<AloxObject>> {
         match val {
             EnvValue::Object(obj) => obj,
@@ -1,34 +1,71 @@
 use thiserror::Error;
 
-use crate::ast::{AloxObject, Expr};
+use crate::{
+    ast::{AloxObject, Expr, Value},
+    functions::CallableRef,
+};
 // TODO Newtype for lines in code
 
 #[derive(Error, Debug)]
 pub enum ParserError {
     #[error("Parsing Failed.")]
     Generic,
-    #[error("[line {line}], found '{lexeme}'. {msg}")]
+    #[error("[line {line}, column {column}], found '{lexeme}'. {msg}")]
     UnexpectedToken {
         line: usize,
+        column: usize,
         lexeme: String,
         msg: String,
     },
     #[error("[line {line}] at end of line: {msg}.")]
     UnexpectedEof { line: usize, msg: String },
+    #[error("[line {line}] at '{lexeme}': Invalid assignment target.")]
+    InvalidAssignmentTarget { line: usize, lexeme: String },
     #[error("[line {line}] Function cannot have more than 255 arguments.")]
     FunctionArgumentLength { line: usize },
     #[error("[line {line}] Function cannot have more than 255 parameters.")]
     FunctionParameterLength { line: usize },
+    #[error("[line {line}] Parameter '{lexeme}' needs a default value, as an earlier parameter has one.")]
+    MissingDefault { line: usize, lexeme: String },
+    #[error("[line {line}] Rest parameter '{lexeme}' must be the last parameter.")]
+    RestNotLast { line: usize, lexeme: String },
+    #[error("[line {line}] Setter '{lexeme}' must take exactly one parameter.")]
+    SetterArity { line: usize, lexeme: String },
+    #[error("[line {line}] A switch can only have one 'default' case.")]
+    DuplicateDefault { line: usize },
 }
 
 #[derive(Error, Debug)]
 pub enum SyntaxError {
-    #[error("[line {line}] Unexpected token, found '{found}'.")]
-    UnexpectedToken { line: usize, found: String },
-    #[error("[line {line}] Unterminated string.")]
-    UnterminatedString { line: usize },
+    #[error("[line {line}, column {column}] Unexpected token, found '{found}'.")]
+    UnexpectedToken {
+        line: usize,
+        column: usize,
+        found: String,
+    },
+    #[error("[line {line}, column {column}] Unterminated string.")]
+    UnterminatedString { line: usize, column: usize },
+    #[error("[line {line}, column {column}] Integer literal '{literal}' is too large.")]
+    IntegerTooLarge {
+        line: usize,
+        column: usize,
+        literal: String,
+    },
+    #[error("[line {line}, column {column}] Malformed number literal '{literal}': {msg}.")]
+    MalformedNumber {
+        line: usize,
+        column: usize,
+        literal: String,
+        msg: String,
+    },
 }
+
+/// Every error found while scanning a source, in the order they were found.
 #[derive(Error, Debug)]
+#[error("{}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"))]
+pub struct SyntaxErrors(pub Vec<SyntaxError>);
+
+#[derive(Error, Debug, is_enum_variant)]
 pub enum RuntimeException {
     #[error("[line {line}] '{lexeme}' operands must be {expected}.")]
     InvalidOperand {
@@ -39,9 +76,15 @@ pub enum RuntimeException {
     #[error("[line {line}] '{lexeme}' not available for {expression:?}.")]
     InvalidOperator {
         lexeme: String,
-        expression: Expr,
+        expression: Box<Expr>,
         line: usize,
     },
+    #[error("[line {line}] '{lexeme}' right operand must not be zero.")]
+    DivisionByZero { lexeme: String, line: usize },
+    #[error("[line {line}] Integer overflow in '{lexeme}'.")]
+    IntegerOverflow { lexeme: String, line: usize },
+    #[error("{function}() {msg}.")]
+    NativeError { function: String, msg: String },
     #[error("[line {line}] Variable '{lexeme}' is undefined.")]
     UndefinedVariable { lexeme: String, line: usize },
     #[error("[line {line}] Tried to use nil variable '{lexeme}'")]
@@ -55,10 +98,148 @@ pub enum RuntimeException {
         line: Option<usize>,
         lexeme: Option<String>,
     },
+    #[error("[line {line}] {function}() expects {expected} but got {found}.")]
+    ArityMismatch {
+        function: String,
+        expected: String,
+        found: usize,
+        line: usize,
+    },
     #[error("[line {line}] Expected a function at '{lexeme}")]
     ExpectedFunction { lexeme: String, line: usize },
+    #[error("[line {line}] Cannot assign to constant '{lexeme}'.")]
+    AssignToConstant { lexeme: String, line: usize },
+    #[error("[line {line}] Cannot redeclare constant '{lexeme}'.")]
+    RedeclareConstant { lexeme: String, line: usize },
+    #[error("[line {line}] Undefined property '{lexeme}'.")]
+    UndefinedProperty { lexeme: String, line: usize },
+    #[error("[line {line}] Could not import '{path}': {msg}")]
+    ImportError {
+        path: String,
+        msg: String,
+        line: usize,
+    },
+    #[error("[line {line}] Cannot iterate over a {kind} in 'for ({lexeme} in ...)'.")]
+    NotIterable {
+        kind: String,
+        lexeme: String,
+        line: usize,
+    },
+    #[error("[line {line}] Cannot destructure: {msg}.")]
+    DestructureMismatch { msg: String, line: usize },
+    #[error("[line {line}] No match arm matches {value}.")]
+    NoMatch { value: String, line: usize },
+    #[error("[line {line}] Cannot set property '{lexeme}' on a {kind}.")]
+    InvalidPropertyTarget {
+        kind: String,
+        lexeme: String,
+        line: usize,
+    },
+    #[error("[line {line}] Cannot index into a {kind} with a {index}.")]
+    NotIndexable {
+        kind: String,
+        index: String,
+        line: usize,
+    },
+    #[error("[line {line}] Cannot collect a {kind} into the rest list '{lexeme}'.")]
+    NotCollectable {
+        kind: String,
+        lexeme: String,
+        line: usize,
+    },
+    #[error("[line {line}] Cannot assign to a {kind} indexed by a {index}.")]
+    NotIndexAssignable {
+        kind: String,
+        index: String,
+        line: usize,
+    },
+    #[error("[line {line}] Index {index} is out of range for length {length}.")]
+    IndexOutOfRange {
+        index: i64,
+        length: usize,
+        line: usize,
+    },
+    #[error("[line {line}] Superclass of '{lexeme}' must be a class, found {kind}.")]
+    InvalidSuperclass {
+        lexeme: String,
+        kind: String,
+        line: usize,
+    },
+    #[error("[line {line}] Class '{class}' can only mix in traits, found {kind}.")]
+    NotATrait {
+        class: String,
+        kind: String,
+        line: usize,
+    },
+    #[error("[line {line}] Class '{class}' gets method '{method}' from both '{first}' and '{second}'; define it in the class to choose one.")]
+    TraitConflict {
+        class: String,
+        method: String,
+        first: String,
+        second: String,
+        line: usize,
+    },
+    #[error("[line {line}] Property '{lexeme}' has a getter but no setter.")]
+    ReadOnlyProperty { lexeme: String, line: usize },
+    #[error("[line {line}] '{lexeme}' in a pattern is not an enum variant.")]
+    NotAVariant { lexeme: String, line: usize },
+    #[error("[line {line}] Import cycle detected: {cycle}")]
+    ImportCycle { cycle: String, line: usize },
     #[error("Returning {obj:?}")]
     Return { obj: AloxObject },
+    /// Returned by `return f(...)` in place of calling `f`, so the caller makes the call instead.
+    #[error("[line {line}] Tail call escaped its caller. This is likely an interpreter error.")]
+    TailCall {
+        function: CallableRef,
+        arguments: Vec<AloxObject>,
+        line: usize,
+    },
+    #[error("[line {line}] Uncaught exception: {value}")]
+    Throw { value: Value, line: usize },
+    /// Raised by `?.` on nil and caught by the enclosing optional chain, which becomes nil.
+    #[error("Optional chain escaped its expression. This is likely an interpreter error.")]
+    ShortCircuit,
+}
+
+impl RuntimeException {
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            RuntimeException::InvalidOperand { line, .. }
+            | RuntimeException::InvalidOperator { line, .. }
+            | RuntimeException::DivisionByZero { line, .. }
+            | RuntimeException::IntegerOverflow { line, .. }
+            | RuntimeException::UndefinedVariable { line, .. }
+            | RuntimeException::NilAccess { line, .. }
+            | RuntimeException::ExpectedFunction { line, .. }
+            | RuntimeException::ArityMismatch { line, .. }
+            | RuntimeException::AssignToConstant { line, .. }
+            | RuntimeException::RedeclareConstant { line, .. }
+            | RuntimeException::UndefinedProperty { line, .. }
+            | RuntimeException::ImportError { line, .. }
+            | RuntimeException::ImportCycle { line, .. }
+            | RuntimeException::NotIterable { line, .. }
+            | RuntimeException::DestructureMismatch { line, .. }
+            | RuntimeException::NoMatch { line, .. }
+            | RuntimeException::InvalidPropertyTarget { line, .. }
+            | RuntimeException::NotIndexable { line, .. }
+            | RuntimeException::NotIndexAssignable { line, .. }
+            | RuntimeException::NotCollectable { line, .. }
+            | RuntimeException::IndexOutOfRange { line, .. }
+            | RuntimeException::NotAVariant { line, .. }
+            | RuntimeException::ReadOnlyProperty { line, .. }
+            | RuntimeException::InvalidSuperclass { line, .. }
+            | RuntimeException::NotATrait { line, .. }
+            | RuntimeException::TraitConflict { line, .. }
+            | RuntimeException::TailCall { line, .. }
+            | RuntimeException::Throw { line, .. } => Some(*line),
+            RuntimeException::ValueMissing { line, .. } => *line,
+            RuntimeException::EnvironmentError
+            | RuntimeException::ControlFlowError
+            | RuntimeException::NativeError { .. }
+            | RuntimeException::Return { .. }
+            | RuntimeException::ShortCircuit => None,
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -68,15 +249,80 @@ pub enum AyloxError {
     #[error("Error: {0}.")]
     GenericError(String),
     #[error("Syntax error: {0}")]
-    SyntaxError(#[from] SyntaxError),
-    #[error("Parsing failed")]
+    SyntaxError(#[from] SyntaxErrors),
+    #[error("Parsing failed: {0}")]
     ParserError(#[from] ParserError),
     #[error("Runtime error: {0}")]
     RuntimeError(#[from] RuntimeException),
+    #[error("Resolution failed: {0}")]
+    ResolverError(#[from] ResolverError),
+    #[error("Type check failed: {0}")]
+    TypeError(#[from] TypeError),
 }
 
 #[derive(Debug, Error)]
 pub enum ResolverError {
     #[error("[line {line}] Can't read local variable in its own initializer at '{lexeme}")]
     ReadInOwnInitializer { lexeme: String, line: usize },
+    #[error("[line {line}] Cannot assign to constant '{lexeme}'.")]
+    AssignToConstant { lexeme: String, line: usize },
+    #[error("[line {line}] Cannot redeclare constant '{lexeme}'.")]
+    RedeclareConstant { lexeme: String, line: usize },
+    #[error("[line {line}] Can't use 'yield' outside of a function.")]
+    YieldOutsideFunction { line: usize },
+    #[error("[line {line}] Can't use 'defer' outside of a function or block.")]
+    DeferOutsideBlock { line: usize },
+    #[error("[line {line}] Can't use 'defer' in a generator.")]
+    DeferInGenerator { line: usize },
+    #[error("[line {line}] Can't use 'this' outside of a class.")]
+    ThisOutsideClass { line: usize },
+    #[error("[line {line}] Can't use 'this' in a class method.")]
+    ThisInClassMethod { line: usize },
+    #[error("[line {line}] A class can't inherit from itself.")]
+    InheritFromSelf { line: usize },
+    #[error("[line {line}] Can't use 'super' outside of a class.")]
+    SuperOutsideClass { line: usize },
+    #[error("[line {line}] Can't use 'super' in a class with no superclass.")]
+    SuperWithoutSuperclass { line: usize },
+    #[error("[line {line}] Can't use 'super' in a trait, which has no superclass.")]
+    SuperInTrait { line: usize },
+    #[error("[line {line}] Can't use 'super' in a class method.")]
+    SuperInClassMethod { line: usize },
+}
+
+/// Problems the `Resolver` reports without stopping the program from running.
+#[derive(Debug, Error)]
+pub enum ResolverWarning {
+    #[error("[line {line}] Match on enum '{enum_name}' does not cover {missing}.")]
+    NonExhaustiveMatch {
+        enum_name: String,
+        missing: String,
+        line: usize,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum TypeError {
+    #[error("[line {line}] Unknown type '{lexeme}'.")]
+    UnknownType { lexeme: String, line: usize },
+    #[error("[line {line}] Expected {expected} for {context} but found {found}.")]
+    Mismatch {
+        expected: String,
+        found: String,
+        context: String,
+        line: usize,
+    },
+    #[error("[line {line}] '{lexeme}' cannot be applied to {left} and {right}.")]
+    InvalidOperands {
+        lexeme: String,
+        left: String,
+        right: String,
+        line: usize,
+    },
+    #[error("[line {line}] '{lexeme}' cannot be applied to {operand}.")]
+    InvalidOperand {
+        lexeme: String,
+        operand: String,
+        line: usize,
+    },
 }
@@ -1,11 +1,22 @@
 use core::fmt::Debug;
 use std::{cell::RefCell, fmt::Display, rc::Rc};
 
-use crate::{ast::*, environment::Environment, error::RuntimeException, interpreter::Interpreter};
+use crate::{
+    ast::*, environment::Environment, error::RuntimeException, generators::Generator,
+    interpreter::Interpreter,
+};
+
+pub type CallableRef = Rc<dyn Callable>;
 
 pub trait Callable {
     fn needs_mut(&self) -> bool;
+    fn name(&self) -> &str;
+    /// The number of arguments that must be passed.
     fn arity(&self) -> usize;
+    /// The most arguments that can be passed, or `None` if any number of extra ones is collected.
+    fn max_arity(&self) -> Option<usize> {
+        Some(self.arity())
+    }
     fn call(&self, interpreter: &Interpreter, args: &[AloxObject]) -> AloxObjResult;
     fn call_mut(&self, interpreter: &mut Interpreter, args: &[AloxObject]) -> AloxObjResult;
 }
@@ -16,25 +27,91 @@ pub struct AloxFunction {
     closure: Rc<RefCell<Environment>>,
 }
 
+impl AloxFunction {
+    /// The line the function is declared on.
+    pub fn line(&self) -> usize {
+        self.declaration.name.line
+    }
+
+    /// This method with `this` bound to `instance`.
+    pub fn bind(&self, instance: InstanceValue) -> AloxFunction {
+        let mut environment = Environment::with_enclosing(self.closure.clone());
+        environment.define("this", Some(AloxObject::Value(Value::Instance(instance))));
+        AloxFunction::new(self.declaration.clone(), Rc::new(RefCell::new(environment)))
+    }
+}
+
 impl Callable for AloxFunction {
     fn needs_mut(&self) -> bool {
         true
     }
+
+    fn name(&self) -> &str {
+        &self.declaration.name.lexeme
+    }
+
     fn arity(&self) -> usize {
-        self.declaration.params.len()
+        self.declaration.params.len() - self.declaration.defaults.len()
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        match self.declaration.rest {
+            Some(_) => None,
+            None => Some(self.declaration.params.len()),
+        }
     }
 
     fn call(&self, _interpreter: &Interpreter, _args: &[AloxObject]) -> AloxObjResult {
         todo!()
     }
 
+    /// Binds the arguments, which the interpreter has already checked against the arity.
+    /// Calling a generator function only binds them, returning a generator that runs the body.
+    /// Missing trailing arguments take their parameter's default, evaluated at call time
+    /// after the parameters before it are bound, and extra ones are collected into the rest list.
     fn call_mut(&self, interpreter: &mut Interpreter, args: &[AloxObject]) -> AloxObjResult {
-        let mut environment = Environment::with_enclosing(self.closure.clone());
-        for (i, param) in self.declaration.params.iter().enumerate() {
-            environment.define(&param.lexeme, Some(args[i].clone()));
+        let declaration = &self.declaration;
+        let environment = Rc::new(RefCell::new(Environment::with_enclosing(
+            self.closure.clone(),
+        )));
+        for (i, param) in declaration.params.iter().enumerate() {
+            let value = match args.get(i) {
+                Some(arg) => arg.clone(),
+                None => {
+                    let default = &declaration.defaults[i - self.arity()];
+                    interpreter.with_environment(environment.clone(), |interpreter| {
+                        interpreter.visit_expr(default)
+                    })?
+                }
+            };
+            environment.borrow_mut().define(&param.lexeme, Some(value));
+        }
+        if let Some(rest) = &declaration.rest {
+            // lists only hold values, so functions, classes and the like can't be collected
+            let extra = args
+                .iter()
+                .skip(declaration.params.len())
+                .map(|arg| match arg {
+                    AloxObject::Value(value) => Ok(value.clone()),
+                    other => Err(RuntimeException::NotCollectable {
+                        kind: other.type_name().to_string(),
+                        lexeme: rest.lexeme.clone(),
+                        line: rest.line,
+                    }),
+                })
+                .collect::<Result<Vec<Value>, RuntimeException>>()?;
+            environment.borrow_mut().define(
+                &rest.lexeme,
+                Some(AloxObject::Value(Value::List(Rc::new(RefCell::new(extra))))),
+            );
         }
 
-        let result = interpreter.interpret_block(&self.declaration.body, environment);
+        if declaration.generator {
+            let generator = Generator::new(declaration.body.clone(), environment);
+            return Ok(AloxObject::Generator(Rc::new(RefCell::new(generator))));
+        }
+
+        let result = interpreter.interpret_function_body(&declaration.body, environment);
         if let Err(err) = result {
             if let RuntimeException::Return { obj: val } = err {
                 Ok(val)
@@ -58,3 +135,13 @@ impl Debug for dyn Callable {
         write!(f, "{}", self.arity())
     }
 }
+
+/// Describes how many arguments a function takes, e.g. `1 argument` or `2 to 3 arguments`.
+pub fn expected_arguments(min: usize, max: Option<usize>) -> String {
+    let plural = |count: usize| if count == 1 { "argument" } else { "arguments" };
+    match max {
+        Some(max) if max == min => format!("{} {}", min, plural(min)),
+        Some(max) => format!("{} to {} {}", min, max, plural(max)),
+        None => format!("at least {} {}", min, plural(min)),
+    }
+}
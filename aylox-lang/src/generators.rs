@@ -0,0 +1,495 @@
+use std::{cell::RefCell, mem, rc::Rc};
+
+use crate::{
+    ast::*,
+    environment::Environment,
+    error::RuntimeException,
+    functions::Callable,
+    interpreter::{caught_value, is_truthy, Interpreter, Iteration},
+    token::Token,
+};
+
+type Env = Rc<RefCell<Environment>>;
+type Statements = Rc<Vec<Stmt>>;
+
+/// The paused body of a call to a function containing `yield`.
+///
+/// The interpreter runs statements by recursing through `visit_stmt`, which cannot be
+/// suspended halfway. A generator instead keeps its own stack of frames for the statements
+/// that contain a `yield`, so it can stop at a `yield` and carry on from there on the next
+/// call to `next()`. Statements without a `yield` are still run by the interpreter directly.
+#[derive(Debug)]
+pub struct Generator {
+    frames: Vec<Frame>,
+    running: bool,
+    /// Whether the last call to `next()` found the body finished rather than at a `yield`.
+    done: bool,
+}
+
+#[derive(Debug)]
+enum Frame {
+    Statements {
+        statements: Statements,
+        next: usize,
+        environment: Env,
+    },
+    While {
+        condition: Expr,
+        body: Statements,
+        environment: Env,
+    },
+    ForIn {
+        name: Token,
+        iteration: Iteration,
+        body: Statements,
+        environment: Env,
+    },
+    Try {
+        catch_name: Option<Token>,
+        catch_body: Statements,
+        finally_body: Statements,
+        stage: TryStage,
+        /// The error or `return` that interrupted the `try`, raised again after `finally`.
+        pending: Option<RuntimeException>,
+        environment: Env,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TryStage {
+    Body,
+    Catch,
+    Finally,
+}
+
+impl Generator {
+    /// A generator that has not started running `body` in `environment` yet.
+    pub fn new(body: Vec<Stmt>, environment: Env) -> Self {
+        Self {
+            frames: vec![Frame::Statements {
+                statements: Rc::new(body),
+                next: 0,
+                environment,
+            }],
+            running: false,
+            done: false,
+        }
+    }
+}
+
+/// Runs `generator` until its next `yield`, returning the yielded object,
+/// or `None` once the body has finished.
+pub fn resume(
+    generator: &Rc<RefCell<Generator>>,
+    interpreter: &mut Interpreter,
+) -> Result<Option<AloxObject>, RuntimeException> {
+    // the frames are taken out while running, so the body may hold the generator itself
+    let mut frames = {
+        let mut generator = generator.borrow_mut();
+        if generator.running {
+            return Err(RuntimeException::NativeError {
+                function: "next".to_string(),
+                msg: "cannot resume a generator that is already running".to_string(),
+            });
+        }
+        generator.running = true;
+        mem::take(&mut generator.frames)
+    };
+    let result = run(&mut frames, interpreter);
+    let mut generator = generator.borrow_mut();
+    generator.running = false;
+    generator.done = !matches!(result, Ok(Some(_)));
+    generator.frames = frames;
+    result
+}
+
+fn run(
+    frames: &mut Vec<Frame>,
+    interpreter: &mut Interpreter,
+) -> Result<Option<AloxObject>, RuntimeException> {
+    while !frames.is_empty() {
+        match step(frames, interpreter) {
+            Ok(Some(yielded)) => return Ok(Some(yielded)),
+            Ok(None) => {}
+            Err(err) => match unwind(frames, err) {
+                Ok(()) => {}
+                // returning from a generator finishes it, discarding the returned value
+                Err(err) if err.is_return() => {}
+                Err(RuntimeException::TailCall {
+                    function,
+                    arguments,
+                    line,
+                }) => {
+                    frames.clear();
+                    interpreter.call_function(function, &arguments, line)?;
+                }
+                Err(err) => {
+                    frames.clear();
+                    return Err(err);
+                }
+            },
+        }
+    }
+    Ok(None)
+}
+
+/// Advances the innermost frame by one statement or loop iteration.
+fn step(
+    frames: &mut Vec<Frame>,
+    interpreter: &mut Interpreter,
+) -> Result<Option<AloxObject>, RuntimeException> {
+    let frame = match frames.last_mut() {
+        Some(frame) => frame,
+        None => return Ok(None),
+    };
+    match frame {
+        Frame::Statements {
+            statements,
+            next,
+            environment,
+        } => {
+            let (statements, index, environment) = (statements.clone(), *next, environment.clone());
+            *next += 1;
+            match statements.get(index) {
+                Some(statement) => execute(frames, interpreter, statement, environment),
+                None => {
+                    frames.pop();
+                    Ok(None)
+                }
+            }
+        }
+        Frame::While {
+            condition,
+            body,
+            environment,
+        } => {
+            let condition = interpreter
+                .with_environment(environment.clone(), |interpreter| {
+                    interpreter.visit_expr(condition)
+                })?
+                .to_value()?;
+            if is_truthy(&condition) {
+                let frame = Frame::Statements {
+                    statements: body.clone(),
+                    next: 0,
+                    environment: Rc::new(RefCell::new(Environment::with_enclosing(
+                        environment.clone(),
+                    ))),
+                };
+                frames.push(frame);
+            } else {
+                frames.pop();
+            }
+            Ok(None)
+        }
+        Frame::ForIn {
+            name,
+            iteration,
+            body,
+            environment,
+        } => {
+            let value = interpreter.with_environment(environment.clone(), |interpreter| {
+                iteration.next(interpreter, name)
+            })?;
+            match value {
+                Some(value) => {
+                    let mut iteration_env = Environment::with_enclosing(environment.clone());
+                    iteration_env.define(&name.lexeme, Some(AloxObject::Value(value)));
+                    let frame = Frame::Statements {
+                        statements: body.clone(),
+                        next: 0,
+                        environment: Rc::new(RefCell::new(iteration_env)),
+                    };
+                    frames.push(frame);
+                }
+                None => {
+                    frames.pop();
+                }
+            }
+            Ok(None)
+        }
+        // the current stage of the `try` finished without an error
+        Frame::Try {
+            finally_body,
+            stage,
+            pending,
+            environment,
+            ..
+        } => {
+            match stage {
+                TryStage::Body | TryStage::Catch => {
+                    *stage = TryStage::Finally;
+                    let frame = block_frame(finally_body.clone(), environment);
+                    frames.push(frame);
+                }
+                TryStage::Finally => {
+                    let pending = pending.take();
+                    frames.pop();
+                    if let Some(err) = pending {
+                        return Err(err);
+                    }
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Runs `statement`, pushing a frame for it if it contains a `yield`.
+fn execute(
+    frames: &mut Vec<Frame>,
+    interpreter: &mut Interpreter,
+    statement: &Stmt,
+    environment: Env,
+) -> Result<Option<AloxObject>, RuntimeException> {
+    if !contains_yield(statement) {
+        interpreter
+            .with_environment(environment, |interpreter| interpreter.visit_stmt(statement))?;
+        return Ok(None);
+    }
+
+    match statement {
+        Stmt::Yield_(yield_) => {
+            let value = match &yield_.value {
+                Some(value) => interpreter
+                    .with_environment(environment, |interpreter| interpreter.visit_expr(value))?,
+                None => AloxObject::Value(Value::Nil(Nil)),
+            };
+            return Ok(Some(value));
+        }
+        Stmt::Block(block) => {
+            frames.push(block_frame(Rc::new(block.statements.clone()), &environment));
+        }
+        Stmt::If_(if_) => {
+            let condition = interpreter
+                .with_environment(environment.clone(), |interpreter| {
+                    interpreter.visit_expr(&if_.condition)
+                })?
+                .to_value()?;
+            let branch = if is_truthy(&condition) {
+                Some(&if_.then_branch)
+            } else {
+                if_.else_branch.as_ref()
+            };
+            if let Some(branch) = branch {
+                frames.push(Frame::Statements {
+                    statements: Rc::new(vec![(**branch).clone()]),
+                    next: 0,
+                    environment,
+                });
+            }
+        }
+        Stmt::While_(while_) => frames.push(Frame::While {
+            condition: while_.condition.clone(),
+            body: body_statements(&while_.body),
+            environment,
+        }),
+        Stmt::ForIn(for_in) => {
+            let iteration = interpreter.with_environment(environment.clone(), |interpreter| {
+                let iterable = interpreter.visit_expr(&for_in.iterable)?;
+                interpreter.iteration(&for_in.name, iterable)
+            })?;
+            frames.push(Frame::ForIn {
+                name: for_in.name.clone(),
+                iteration,
+                body: body_statements(&for_in.body),
+                environment,
+            });
+        }
+        Stmt::MatchStmt(match_stmt) => {
+            let (body, arm_env) = interpreter.with_environment(environment, |interpreter| {
+                interpreter.select_arm(&match_stmt.keyword, &match_stmt.subject, &match_stmt.arms)
+            })?;
+            frames.push(Frame::Statements {
+                statements: Rc::new(vec![body.clone()]),
+                next: 0,
+                environment: Rc::new(RefCell::new(arm_env)),
+            });
+        }
+        Stmt::Switch(switch) => {
+            let body = interpreter
+                .with_environment(environment.clone(), |interpreter| {
+                    interpreter.select_case(switch)
+                })?
+                .cloned();
+            frames.push(Frame::Statements {
+                statements: Rc::new(body.into_iter().collect()),
+                next: 0,
+                environment,
+            });
+        }
+        Stmt::Try_(try_) => {
+            let body = block_frame(Rc::new(try_.body.clone()), &environment);
+            frames.push(Frame::Try {
+                catch_name: try_.catch_name.clone(),
+                catch_body: Rc::new(try_.catch_body.clone()),
+                finally_body: Rc::new(try_.finally_body.clone()),
+                stage: TryStage::Body,
+                pending: None,
+                environment,
+            });
+            frames.push(body);
+        }
+        _ => interpreter
+            .with_environment(environment, |interpreter| interpreter.visit_stmt(statement))?,
+    }
+    Ok(None)
+}
+
+/// Pops frames until a `try` handles `err`, mirroring `Interpreter::visit_try_`:
+/// `catch` sees errors but not `return`, and `finally` runs before either carries on.
+/// Returns the error if no frame handles it.
+fn unwind(frames: &mut Vec<Frame>, err: RuntimeException) -> Result<(), RuntimeException> {
+    while let Some(frame) = frames.pop() {
+        if let Frame::Try {
+            catch_name,
+            catch_body,
+            finally_body,
+            stage,
+            environment,
+            ..
+        } = frame
+        {
+            let (stage, pending, handler) = match (stage, &catch_name) {
+                (TryStage::Body, Some(catch_name)) if !err.is_return() => {
+                    let mut catch_env = Environment::with_enclosing(environment.clone());
+                    catch_env.define(
+                        &catch_name.lexeme,
+                        Some(AloxObject::Value(caught_value(err))),
+                    );
+                    let handler = Frame::Statements {
+                        statements: catch_body.clone(),
+                        next: 0,
+                        environment: Rc::new(RefCell::new(catch_env)),
+                    };
+                    (TryStage::Catch, None, handler)
+                }
+                (TryStage::Body, _) | (TryStage::Catch, _) => (
+                    TryStage::Finally,
+                    Some(err),
+                    block_frame(finally_body.clone(), &environment),
+                ),
+                // an error inside `finally` replaces the one it was handling
+                (TryStage::Finally, _) => continue,
+            };
+            frames.push(Frame::Try {
+                catch_name,
+                catch_body,
+                finally_body,
+                stage,
+                pending,
+                environment,
+            });
+            frames.push(handler);
+            return Ok(());
+        }
+    }
+    Err(err)
+}
+
+fn block_frame(statements: Statements, enclosing: &Env) -> Frame {
+    Frame::Statements {
+        statements,
+        next: 0,
+        environment: Rc::new(RefCell::new(Environment::with_enclosing(enclosing.clone()))),
+    }
+}
+
+/// The statements of a loop body, which gets a fresh environment on every iteration.
+fn body_statements(body: &Stmt) -> Statements {
+    match body {
+        Stmt::Block(block) => Rc::new(block.statements.clone()),
+        body => Rc::new(vec![body.clone()]),
+    }
+}
+
+/// Whether `statement` can suspend the generator. A `yield` inside a nested
+/// function declaration belongs to that function instead.
+fn contains_yield(statement: &Stmt) -> bool {
+    match statement {
+        Stmt::Yield_(_) => true,
+        Stmt::Block(block) => block.statements.iter().any(contains_yield),
+        Stmt::If_(if_) => {
+            contains_yield(&if_.then_branch)
+                || if_.else_branch.as_deref().is_some_and(contains_yield)
+        }
+        Stmt::While_(while_) => contains_yield(&while_.body),
+        Stmt::ForIn(for_in) => contains_yield(&for_in.body),
+        Stmt::MatchStmt(match_stmt) => match_stmt.arms.iter().any(|arm| contains_yield(&arm.body)),
+        Stmt::Switch(switch) => switch
+            .cases
+            .iter()
+            .map(|case| &case.body)
+            .chain(switch.default_branch.as_deref())
+            .any(contains_yield),
+        Stmt::Try_(try_) => try_
+            .body
+            .iter()
+            .chain(try_.catch_body.iter())
+            .chain(try_.finally_body.iter())
+            .any(contains_yield),
+        _ => false,
+    }
+}
+
+/// The methods of a generator object: `next()` and `done()`.
+pub fn generator_method(
+    generator: &Rc<RefCell<Generator>>,
+    name: &str,
+) -> Option<Rc<dyn Callable>> {
+    match name {
+        "next" => Some(Rc::new(GeneratorNext(generator.clone()))),
+        "done" => Some(Rc::new(GeneratorDone(generator.clone()))),
+        _ => None,
+    }
+}
+
+/// The `next` method of a generator object, which returns `nil` once the body has finished.
+pub struct GeneratorNext(pub Rc<RefCell<Generator>>);
+
+impl Callable for GeneratorNext {
+    fn needs_mut(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "next"
+    }
+
+    fn arity(&self) -> usize {
+        0
+    }
+
+    fn call(&self, _interpreter: &Interpreter, _args: &[AloxObject]) -> AloxObjResult {
+        unreachable!("a generator's next() is called through call_mut")
+    }
+
+    fn call_mut(&self, interpreter: &mut Interpreter, _args: &[AloxObject]) -> AloxObjResult {
+        Ok(resume(&self.0, interpreter)?.unwrap_or(AloxObject::Value(Value::Nil(Nil))))
+    }
+}
+
+/// The `done` method of a generator object, telling a yielded `nil` apart from the end.
+pub struct GeneratorDone(pub Rc<RefCell<Generator>>);
+
+impl Callable for GeneratorDone {
+    fn needs_mut(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        "done"
+    }
+
+    fn arity(&self) -> usize {
+        0
+    }
+
+    fn call(&self, _interpreter: &Interpreter, _args: &[AloxObject]) -> AloxObjResult {
+        Ok(AloxObject::Value(Value::Bool(self.0.borrow().done)))
+    }
+
+    fn call_mut(&self, _interpreter: &mut Interpreter, _args: &[AloxObject]) -> AloxObjResult {
+        unreachable!("a generator's done() is called through call")
+    }
+}
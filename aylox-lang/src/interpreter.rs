@@ -1,24 +1,79 @@
-use std::{cell::RefCell, mem, rc::Rc};
+use std::{
+    cell::RefCell,
+    convert::TryFrom,
+    fs, mem, ops,
+    path::{Path, PathBuf},
+    rc::Rc,
+    slice, vec,
+};
 
 use crate::{
-    ast::*, ast_printer::AstPrinter, environment::Environment, error::RuntimeException,
-    functions::AloxFunction, native_functions::Clock, token::TokenType,
+    ast::*,
+    ast_printer::AstPrinter,
+    checker::Checker,
+    classes::{mix_in, AloxClass, AloxTrait},
+    enums::EnumType,
+    environment::Environment,
+    error::{ResolverWarning, RuntimeException},
+    functions::{expected_arguments, AloxFunction, Callable},
+    generators::generator_method,
+    modules::{resolve_import, Module, ModuleCache},
+    native_functions::{Clock, Float, Int},
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    token::{Token, TokenType},
 };
 
 pub struct Interpreter {
     printer: AstPrinter,
     pub global_env: Rc<RefCell<Environment>>,
+    script_path: Option<PathBuf>,
+    modules: ModuleCache,
+    /// The expressions deferred by each block the current function call is running,
+    /// innermost block last.
+    defers: Vec<Vec<Expr>>,
+    /// Warnings found resolving imported modules, left for the caller to report.
+    warnings: Vec<ResolverWarning>,
+    /// The line of the call expression being made, for callables that call other functions
+    /// on its behalf.
+    pub(crate) call_line: usize,
 }
 impl Interpreter {
     pub fn new() -> Self {
-        let mut globals = Environment::new();
-        globals.define("clock", Some(AloxObject::Function(Rc::new(Clock))));
         Self {
             printer: AstPrinter,
-            global_env: Rc::new(RefCell::new(globals)),
+            global_env: Rc::new(RefCell::new(Interpreter::globals())),
+            script_path: None,
+            modules: ModuleCache::default(),
+            defers: Vec::new(),
+            warnings: Vec::new(),
+            call_line: 0,
         }
     }
 
+    /// An interpreter for the script at `path`, which its imports are resolved relative to.
+    pub fn with_script_path(path: &Path) -> Self {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut interpreter = Self::new();
+        interpreter.modules.loading.push(path.clone());
+        interpreter.script_path = Some(path);
+        interpreter
+    }
+
+    /// Warnings found resolving the modules imported so far.
+    pub fn warnings(&self) -> &[ResolverWarning] {
+        &self.warnings
+    }
+
+    fn globals() -> Environment {
+        let mut globals = Environment::new();
+        globals.define("clock", Some(AloxObject::Function(Rc::new(Clock))));
+        globals.define("int", Some(AloxObject::Function(Rc::new(Int))));
+        globals.define("float", Some(AloxObject::Function(Rc::new(Float))));
+        globals
+    }
+
     pub fn interpret(&mut self, statements: &[Stmt]) -> Result<(), RuntimeException> {
         for stmt in statements {
             self.visit_stmt(stmt)?;
@@ -35,14 +90,745 @@ impl Interpreter {
         statements: &[Stmt],
         environment: Environment,
     ) -> Result<(), RuntimeException> {
-        let previous = mem::replace(&mut self.global_env, Rc::new(RefCell::new(environment)));
-        let result = self.interpret(statements);
+        self.interpret_scope(statements, Rc::new(RefCell::new(environment)))
+    }
+
+    /// Runs `statements` in `environment`, then the expressions they deferred, last
+    /// deferred first. Deferred expressions run however the statements finish; an error
+    /// in one takes the place of the block's result unless the block already failed.
+    fn interpret_scope(
+        &mut self,
+        statements: &[Stmt],
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<(), RuntimeException> {
+        self.with_environment(environment, |interpreter| {
+            interpreter.defers.push(vec![]);
+            let mut result = interpreter.interpret(statements);
+            let deferred = interpreter.defers.pop().unwrap_or_default();
+            for expression in deferred.iter().rev() {
+                if let Err(err) = interpreter.visit_expr(expression) {
+                    if result.is_ok() || result.as_ref().is_err_and(is_control_flow) {
+                        result = Err(err);
+                    }
+                }
+            }
+            result
+        })
+    }
+
+    /// Runs the body of a function call, which defers expressions separately from its caller.
+    pub(crate) fn interpret_function_body(
+        &mut self,
+        body: &[Stmt],
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<(), RuntimeException> {
+        let caller_defers = mem::take(&mut self.defers);
+        let result = self.interpret_scope(body, environment);
+        self.defers = caller_defers;
+        result
+    }
+
+    /// Runs `run` with `environment` as the current environment, restoring the previous one after.
+    pub fn with_environment<T>(
+        &mut self,
+        environment: Rc<RefCell<Environment>>,
+        run: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let previous = mem::replace(&mut self.global_env, environment);
+        let result = run(self);
         self.global_env = previous;
         result
     }
 
-    pub fn resolve_expr(&mut self, expr: &Expr, depth: usize) {
-        todo!()
+    /// Reads `target` once, computes its new value with `update` and writes it back,
+    /// returning both the previous and the updated value.
+    fn update_target(
+        &mut self,
+        target: &Expr,
+        operator: &Token,
+        update: impl FnOnce(&mut Self, Value) -> ValueResult,
+    ) -> Result<(Value, Value), RuntimeException> {
+        match target {
+            Expr::Variable(variable) => {
+                let current = self
+                    .visit_variable(variable)?
+                    .to_value_with_info(operator.line, &operator.lexeme)?;
+                let updated = update(self, current.clone())?;
+                self.global_env
+                    .borrow_mut()
+                    .assign(&variable.name, Some(AloxObject::Value(updated.clone())))?;
+                Ok((current, updated))
+            }
+            Expr::Get(get) => {
+                let object = self.visit_expr(&get.object)?;
+                let current = self
+                    .property(object.clone(), &get.name)?
+                    .to_value_with_info(operator.line, &operator.lexeme)?;
+                let updated = update(self, current.clone())?;
+                self.set_property(object, &get.name, AloxObject::Value(updated.clone()))?;
+                Ok((current, updated))
+            }
+            Expr::Index(index) => {
+                let bracket = &index.bracket;
+                let object = self
+                    .visit_expr(&index.object)?
+                    .to_value_with_info(bracket.line, &bracket.lexeme)?;
+                let position = self
+                    .visit_expr(&index.index)?
+                    .to_value_with_info(bracket.line, &bracket.lexeme)?;
+                let current = self
+                    .index(object.clone(), position.clone(), bracket)?
+                    .to_value_with_info(operator.line, &operator.lexeme)?;
+                let updated = update(self, current.clone())?;
+                self.set_index(object, position, updated.clone(), bracket)?;
+                Ok((current, updated))
+            }
+            _ => Err(RuntimeException::InvalidOperator {
+                lexeme: operator.lexeme.clone(),
+                expression: Box::new(target.clone()),
+                line: operator.line,
+            }),
+        }
+    }
+
+    /// Evaluates the module an `import` refers to in its own global environment,
+    /// or returns it from the cache if it has already been imported.
+    fn import_module(&mut self, import: &Import) -> Result<Rc<Module>, RuntimeException> {
+        let line = import.keyword.line;
+        let requested = match &import.path._type {
+            TokenType::String(path) => path.as_str(),
+            _ => import.path.lexeme.as_str(),
+        };
+        let import_error = |msg: String| RuntimeException::ImportError {
+            path: requested.to_string(),
+            msg,
+            line,
+        };
+
+        let path = resolve_import(self.script_path.as_deref(), requested)
+            .canonicalize()
+            .map_err(|err| import_error(err.to_string()))?;
+        if let Some(module) = self.modules.loaded.get(&path) {
+            return Ok(module.clone());
+        }
+        if self.modules.loading.contains(&path) {
+            return Err(RuntimeException::ImportCycle {
+                cycle: self.modules.cycle_to(&path),
+                line,
+            });
+        }
+
+        let contents = fs::read_to_string(&path).map_err(|err| import_error(err.to_string()))?;
+        let mut scanner = Scanner::new(&contents);
+        let tokens = scanner
+            .scan_tokens()
+            .map_err(|err| import_error(err.to_string()))?;
+        let mut parser = Parser::new(tokens);
+        let statements = parser
+            .parse()
+            .map_err(|err| import_error(err.to_string()))?;
+        let mut resolver = Resolver::new();
+        resolver
+            .resolve(&statements)
+            .map_err(|err| import_error(err.to_string()))?;
+        self.warnings.extend(resolver.into_warnings());
+        Checker::new()
+            .check(&statements)
+            .map_err(|err| import_error(err.to_string()))?;
+
+        let environment = Rc::new(RefCell::new(Interpreter::globals()));
+        let previous_env = mem::replace(&mut self.global_env, environment.clone());
+        let previous_path = self.script_path.replace(path.clone());
+        self.modules.loading.push(path.clone());
+        let result = self.interpret(&statements);
+        self.modules.loading.pop();
+        self.script_path = previous_path;
+        self.global_env = previous_env;
+        result?;
+
+        let module = Rc::new(Module::new(path.clone(), environment));
+        self.modules.loaded.insert(path, module.clone());
+        Ok(module)
+    }
+
+    /// The function a call refers to and its evaluated arguments.
+    fn evaluate_call(
+        &mut self,
+        call: &Call,
+    ) -> Result<(Rc<dyn Callable>, Vec<AloxObject>), RuntimeException> {
+        let callee = self.visit_expr(&call.callee)?;
+        if call.optional && is_nil(&callee) {
+            return Err(RuntimeException::ShortCircuit);
+        }
+        let function = callee.to_function(call)?;
+        let mut arguments = vec![];
+        for arg in call.arguments.iter() {
+            arguments.push(self.visit_expr(arg)?);
+        }
+        Ok((function, arguments))
+    }
+
+    /// Calls `function` with `arguments` passed at `line`.
+    ///
+    /// A function ending in `return g(...)` hands the call to `g` back as a `TailCall`
+    /// instead of making it, and this loop makes it in its place, so tail-recursive
+    /// Lox functions run in constant Rust stack.
+    pub(crate) fn call_function(
+        &mut self,
+        function: Rc<dyn Callable>,
+        arguments: &[AloxObject],
+        line: usize,
+    ) -> AloxObjResult {
+        let mut result = self.call_once(function, arguments, line);
+        while let Err(RuntimeException::TailCall {
+            function,
+            arguments,
+            line,
+        }) = result
+        {
+            result = self.call_once(function, &arguments, line);
+        }
+        result
+    }
+
+    /// Calls `function` after checking it accepts the number of `arguments` passed at `line`.
+    fn call_once(
+        &mut self,
+        function: Rc<dyn Callable>,
+        arguments: &[AloxObject],
+        line: usize,
+    ) -> AloxObjResult {
+        let (min, max) = (function.arity(), function.max_arity());
+        if arguments.len() < min || max.is_some_and(|max| arguments.len() > max) {
+            return Err(RuntimeException::ArityMismatch {
+                function: function.name().to_string(),
+                expected: expected_arguments(min, max),
+                found: arguments.len(),
+                line,
+            });
+        }
+        self.call_line = line;
+        if function.needs_mut() {
+            function.call_mut(self, arguments)
+        } else {
+            function.call(self, arguments)
+        }
+    }
+
+    /// The first of `arms` whose pattern matches the value of `subject`, along with
+    /// an environment holding the names its pattern binds.
+    pub(crate) fn select_arm<'a, T>(
+        &mut self,
+        keyword: &Token,
+        subject: &Expr,
+        arms: &'a [Arm<T>],
+    ) -> Result<(&'a T, Environment), RuntimeException> {
+        let value = self
+            .visit_expr(subject)?
+            .to_value_with_info(keyword.line, &keyword.lexeme)?;
+        for arm in arms.iter() {
+            let mut bindings = vec![];
+            if self.matches(&arm.pattern, &value, &mut bindings)? {
+                let mut environment = Environment::with_enclosing(self.global_env.clone());
+                for (name, value) in bindings {
+                    environment.define(&name.lexeme, Some(AloxObject::Value(value)));
+                }
+                return Ok((&arm.body, environment));
+            }
+        }
+        Err(RuntimeException::NoMatch {
+            value: value.to_string(),
+            line: keyword.line,
+        })
+    }
+
+    /// The body of the first case of `switch` holding a value `==` to its subject,
+    /// or its `default` body when there is none. Case values are evaluated in order,
+    /// stopping at the first match.
+    pub(crate) fn select_case<'a>(
+        &mut self,
+        switch: &'a Switch,
+    ) -> Result<Option<&'a Stmt>, RuntimeException> {
+        let keyword = &switch.keyword;
+        let subject = self
+            .visit_expr(&switch.subject)?
+            .to_value_with_info(keyword.line, &keyword.lexeme)?;
+        let equals = Token::new(TokenType::EqualEqual, "==", keyword.line);
+        for case in switch.cases.iter() {
+            for value in case.values.iter() {
+                let candidate = self
+                    .visit_expr(value)?
+                    .to_value_with_info(keyword.line, &keyword.lexeme)?;
+                let equal = self
+                    .binary(&equals, subject.clone(), candidate, || value.clone())?
+                    .to_value_with_info(keyword.line, &keyword.lexeme)?;
+                if is_truthy(&equal) {
+                    return Ok(Some(&case.body));
+                }
+            }
+        }
+        Ok(switch.default_branch.as_deref())
+    }
+
+    /// Whether `value` matches `pattern`, adding the names it binds to `bindings`.
+    fn matches(
+        &self,
+        pattern: &MatchPattern,
+        value: &Value,
+        bindings: &mut Vec<(Token, Value)>,
+    ) -> Result<bool, RuntimeException> {
+        match pattern {
+            MatchPattern::Wildcard(_) => Ok(true),
+            MatchPattern::Literal(_, literal) => Ok(values_equal(literal, value)),
+            MatchPattern::Identifier(name) => {
+                let object = self.global_env.borrow().get(name).ok();
+                match object.as_deref() {
+                    Some(Some(AloxObject::Value(Value::Variant(variant))))
+                        if variant.fields.is_empty() && variant.variant == name.lexeme =>
+                    {
+                        Ok(matches!(value, Value::Variant(value) if value == variant))
+                    }
+                    _ => {
+                        bindings.push((name.clone(), value.clone()));
+                        Ok(true)
+                    }
+                }
+            }
+            MatchPattern::Variant { name, fields } => {
+                if self.global_env.borrow().get(name).is_err() {
+                    return Err(RuntimeException::NotAVariant {
+                        lexeme: name.lexeme.clone(),
+                        line: name.line,
+                    });
+                }
+                let variant = match value {
+                    Value::Variant(variant) if variant.variant == name.lexeme => variant,
+                    _ => return Ok(false),
+                };
+                if variant.fields.len() != fields.len() {
+                    return Err(RuntimeException::DestructureMismatch {
+                        msg: format!(
+                            "{} has {} fields, but the pattern has {}",
+                            name.lexeme,
+                            variant.fields.len(),
+                            fields.len()
+                        ),
+                        line: name.line,
+                    });
+                }
+                for (pattern, field) in fields.iter().zip(variant.fields.iter()) {
+                    if !self.matches(pattern, field, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    /// Assigns the field `name` of `object`, which must be an instance.
+    fn set_property(
+        &mut self,
+        object: AloxObject,
+        name: &Token,
+        value: AloxObject,
+    ) -> Result<(), RuntimeException> {
+        match object {
+            AloxObject::Value(Value::Instance(instance)) => {
+                if let Some(setter) = instance.class.find_setter(&name.lexeme) {
+                    let setter = Rc::new(setter.bind(instance.clone()));
+                    self.call_function(setter, &[value], name.line)?;
+                    return Ok(());
+                }
+                if instance.class.find_getter(&name.lexeme).is_some() {
+                    return Err(RuntimeException::ReadOnlyProperty {
+                        lexeme: name.lexeme.clone(),
+                        line: name.line,
+                    });
+                }
+                instance.set_field(&name.lexeme, value);
+                Ok(())
+            }
+            object => Err(RuntimeException::InvalidPropertyTarget {
+                kind: object.type_name().to_string(),
+                lexeme: name.lexeme.clone(),
+                line: name.line,
+            }),
+        }
+    }
+
+    /// `object[position]`, calling `__index` on instances that declare it.
+    fn index(&mut self, object: Value, position: Value, bracket: &Token) -> AloxObjResult {
+        if let Value::Instance(instance) = &object {
+            let argument = [AloxObject::Value(position.clone())];
+            if let Some(result) = self.overload(instance, "__index", &argument, bracket.line)? {
+                return Ok(result);
+            }
+        }
+        index_value(object, position, bracket).map(AloxObject::Value)
+    }
+
+    /// `object[position] = value` for list elements and map entries, adding the entry if the
+    /// map has no such key. Instances can declare `__setindex(position, value)` to support it.
+    fn set_index(
+        &mut self,
+        object: Value,
+        position: Value,
+        value: Value,
+        bracket: &Token,
+    ) -> Result<(), RuntimeException> {
+        match (&object, position) {
+            (Value::List(list), Value::Integer(position)) => {
+                let mut list = list.borrow_mut();
+                let position = element_position(position, list.len(), bracket)?;
+                list[position] = value;
+                Ok(())
+            }
+            (Value::Map(map), key) => {
+                let mut entries = map.borrow_mut();
+                match entries
+                    .iter_mut()
+                    .find(|(existing, _)| values_equal(existing, &key))
+                {
+                    Some(entry) => entry.1 = value,
+                    None => entries.push((key, value)),
+                }
+                Ok(())
+            }
+            (Value::Instance(instance), position)
+                if instance.class.find_method("__setindex").is_some() =>
+            {
+                let arguments = [AloxObject::Value(position), AloxObject::Value(value)];
+                self.overload(instance, "__setindex", &arguments, bracket.line)?;
+                Ok(())
+            }
+            (_, position) => Err(RuntimeException::NotIndexAssignable {
+                kind: AloxObject::Value(object.clone()).type_name().to_string(),
+                index: AloxObject::Value(position).type_name().to_string(),
+                line: bracket.line,
+            }),
+        }
+    }
+
+    /// Calls the method `name` of `instance` if its class declares one,
+    /// which is how instances overload operators.
+    fn overload(
+        &mut self,
+        instance: &InstanceValue,
+        name: &str,
+        arguments: &[AloxObject],
+        line: usize,
+    ) -> Result<Option<AloxObject>, RuntimeException> {
+        match instance.class.find_method(name) {
+            Some(method) => {
+                let method = Rc::new(method.bind(instance.clone()));
+                self.call_function(method, arguments, line).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Applies a binary operator, calling the method that overloads it if the left operand
+    /// is an instance declaring one, like `__add` for `+`. `!=` negates `__eq`.
+    fn binary(
+        &mut self,
+        operator: &Token,
+        left: Value,
+        right: Value,
+        expression: impl FnOnce() -> Expr,
+    ) -> AloxObjResult {
+        if let (Value::Instance(instance), Some(name)) = (&left, operator_method(operator)) {
+            let argument = [AloxObject::Value(right.clone())];
+            if let Some(result) = self.overload(instance, name, &argument, operator.line)? {
+                if operator._type == TokenType::BangEqual {
+                    let equal = result.to_value_with_info(operator.line, &operator.lexeme)?;
+                    return Ok(AloxObject::Value(Value::Bool(!is_truthy(&equal))));
+                }
+                return Ok(result);
+            }
+        }
+        // strings concatenate with instances declaring `__str`
+        if operator._type == TokenType::Plus {
+            match (&left, &right) {
+                (Value::String(string), Value::Instance(instance)) => {
+                    if let Some(shown) = self.instance_string(instance)? {
+                        return Ok(AloxObject::Value(Value::String(string.clone() + &shown)));
+                    }
+                }
+                (Value::Instance(instance), Value::String(string)) => {
+                    if let Some(shown) = self.instance_string(instance)? {
+                        return Ok(AloxObject::Value(Value::String(shown + string)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        binary_operation(operator, left, right, expression)
+    }
+
+    /// The result of the `__str` method of `instance`, if its class declares one.
+    fn instance_string(
+        &mut self,
+        instance: &InstanceValue,
+    ) -> Result<Option<String>, RuntimeException> {
+        let line = match instance.class.find_method("__str") {
+            Some(method) => method.line(),
+            None => return Ok(None),
+        };
+        match self.overload(instance, "__str", &[], line)? {
+            Some(shown) => Ok(Some(shown.to_value()?.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// How `print` shows `value`.
+    fn stringify(&mut self, value: Value) -> Result<String, RuntimeException> {
+        if let Value::Instance(instance) = &value {
+            if let Some(shown) = self.instance_string(instance)? {
+                return Ok(shown);
+            }
+        }
+        Ok(self.printer.print(&Expr::Literal(Literal { value })))
+    }
+
+    /// Looks up the property `name` on `object`.
+    fn property(&mut self, object: AloxObject, name: &Token) -> AloxObjResult {
+        if let AloxObject::Module(module) = &object {
+            let value = module.environment.borrow().get(name).map_err(|err| {
+                if err.is_undefined_variable() {
+                    RuntimeException::UndefinedProperty {
+                        lexeme: name.lexeme.clone(),
+                        line: name.line,
+                    }
+                } else {
+                    err
+                }
+            })?;
+            return Ok(value.as_ref().clone().unwrap());
+        }
+        if let AloxObject::Value(Value::Instance(instance)) = &object {
+            if let Some(getter) = instance.class.find_getter(&name.lexeme) {
+                let getter = Rc::new(getter.bind(instance.clone()));
+                return self.call_function(getter, &[], name.line);
+            }
+            if let Some(field) = instance.field(&name.lexeme) {
+                return Ok(field);
+            }
+            if let Some(method) = instance.class.find_method(&name.lexeme) {
+                return Ok(AloxObject::Function(Rc::new(method.bind(instance.clone()))));
+            }
+            return Err(RuntimeException::UndefinedProperty {
+                lexeme: name.lexeme.clone(),
+                line: name.line,
+            });
+        }
+        if let AloxObject::Class(class) = &object {
+            return match class.find_class_method(&name.lexeme) {
+                Some(method) => Ok(AloxObject::Function(Rc::new(method.clone()))),
+                None => Err(RuntimeException::UndefinedProperty {
+                    lexeme: name.lexeme.clone(),
+                    line: name.line,
+                }),
+            };
+        }
+        if let AloxObject::Enum(enum_type) = &object {
+            return enum_type.variant(&name.lexeme).cloned().ok_or_else(|| {
+                RuntimeException::UndefinedProperty {
+                    lexeme: name.lexeme.clone(),
+                    line: name.line,
+                }
+            });
+        }
+        if let AloxObject::Generator(generator) = &object {
+            if let Some(method) = generator_method(generator, &name.lexeme) {
+                return Ok(AloxObject::Function(method));
+            }
+        }
+
+        let object = object.to_value_with_info(name.line, &name.lexeme)?;
+        match (object, name.lexeme.as_str()) {
+            (Value::Error(err), "message") => Ok(AloxObject::Value(Value::String(err.message))),
+            (Value::Error(err), "line") => Ok(AloxObject::Value(match err.line {
+                Some(line) => Value::Integer(line as i64),
+                None => Value::Nil(Nil),
+            })),
+            _ => Err(RuntimeException::UndefinedProperty {
+                lexeme: name.lexeme.clone(),
+                line: name.line,
+            }),
+        }
+    }
+
+    /// The method `name` of `object`, if it has one: a generator's `next()` or `done()`,
+    /// a method of an instance's class or a function defined by a module.
+    fn method(object: &AloxObject, name: &str) -> Option<Rc<dyn Callable>> {
+        match object {
+            AloxObject::Generator(generator) => generator_method(generator, name),
+            AloxObject::Value(Value::Instance(instance)) => instance
+                .class
+                .find_method(name)
+                .map(|method| Rc::new(method.bind(instance.clone())) as Rc<dyn Callable>),
+            AloxObject::Module(module) => module.environment.borrow().function(name),
+            _ => None,
+        }
+    }
+
+    /// Pairs every name in `pattern` with the part of `value` it unpacks: list elements by
+    /// position, map entries by key, or the properties of an instance or a module, getters included.
+    fn destructure(
+        &mut self,
+        pattern: &Pattern,
+        value: AloxObject,
+    ) -> Result<Vec<(Token, AloxObject)>, RuntimeException> {
+        let mismatch =
+            |msg: String, line: usize| RuntimeException::DestructureMismatch { msg, line };
+        match (pattern, value) {
+            (Pattern::List { bracket, names }, AloxObject::Value(Value::List(list))) => {
+                let elements = list.borrow();
+                if elements.len() != names.len() {
+                    return Err(mismatch(
+                        format!(
+                            "'{}' expects {} elements, found {}",
+                            pattern,
+                            names.len(),
+                            elements.len()
+                        ),
+                        bracket.line,
+                    ));
+                }
+                Ok(names
+                    .iter()
+                    .cloned()
+                    .zip(elements.iter().cloned().map(AloxObject::Value))
+                    .collect())
+            }
+            (Pattern::Map { brace, names }, AloxObject::Value(Value::Map(map))) => {
+                let entries = map.borrow();
+                names
+                    .iter()
+                    .map(|name| {
+                        let key = Value::String(name.lexeme.clone());
+                        entries
+                            .iter()
+                            .find(|(existing, _)| values_equal(existing, &key))
+                            .map(|(_, value)| (name.clone(), AloxObject::Value(value.clone())))
+                            .ok_or_else(|| {
+                                mismatch(format!("key '{}' is missing", name.lexeme), brace.line)
+                            })
+                    })
+                    .collect()
+            }
+            (Pattern::Map { names, .. }, object)
+                if matches!(object, AloxObject::Value(Value::Instance(_)))
+                    || !matches!(object, AloxObject::Value(_)) =>
+            {
+                names
+                    .iter()
+                    .map(|name| Ok((name.clone(), self.property(object.clone(), name)?)))
+                    .collect()
+            }
+            (Pattern::List { bracket, .. }, value) => Err(mismatch(
+                format!("'{}' expects a list, found {}", pattern, value.type_name()),
+                bracket.line,
+            )),
+            (Pattern::Map { brace, .. }, value) => Err(mismatch(
+                format!("'{}' expects a map, found {}", pattern, value.type_name()),
+                brace.line,
+            )),
+        }
+    }
+
+    /// Runs one iteration of a `for-in` loop, binding the loop variable in a fresh
+    /// environment so closures created in the body capture this iteration's value.
+    fn for_in_iteration(&mut self, for_in: &ForIn, value: Value) -> Result<(), RuntimeException> {
+        let mut environment = Environment::with_enclosing(self.global_env.clone());
+        environment.define(&for_in.name.lexeme, Some(AloxObject::Value(value)));
+        self.interpret_block(slice::from_ref(&*for_in.body), environment)
+    }
+
+    /// Starts iterating `iterable` for the `for-in` loop variable `name`. Lists, strings, maps
+    /// and ranges are built in; any other object goes through the iterator protocol: if it has
+    /// an `iter()` method its result is the iterator, otherwise the object is its own iterator.
+    /// The iterator's `next()` is called until its `done()` returns true, or, for iterators
+    /// without a `done()` method, until `next()` returns `nil`.
+    pub(crate) fn iteration(
+        &mut self,
+        name: &Token,
+        iterable: AloxObject,
+    ) -> Result<Iteration, RuntimeException> {
+        let values = match iterable {
+            AloxObject::Value(Value::List(list)) => list.borrow().clone(),
+            AloxObject::Value(Value::String(string)) => string
+                .chars()
+                .map(|char| Value::String(char.to_string()))
+                .collect(),
+            AloxObject::Value(Value::Map(map)) => {
+                map.borrow().iter().map(|(key, _)| key.clone()).collect()
+            }
+            AloxObject::Value(Value::Range(range)) => return Ok(Iteration::Range(range.values())),
+            iterable => {
+                let iterator = match Self::method(&iterable, "iter") {
+                    Some(iter) => self.call_function(iter, &[], name.line)?,
+                    None => iterable,
+                };
+                return match Self::method(&iterator, "next") {
+                    Some(next) => Ok(Iteration::Protocol {
+                        next,
+                        done: Self::method(&iterator, "done"),
+                    }),
+                    None => Err(not_iterable(name, &iterator)),
+                };
+            }
+        };
+        Ok(Iteration::Values(values.into_iter()))
+    }
+}
+
+/// The elements a `for-in` loop has yet to visit.
+#[derive(Debug)]
+pub(crate) enum Iteration {
+    Values(vec::IntoIter<Value>),
+    Range(ops::RangeInclusive<i64>),
+    /// An iterator object's `next()` method, and its `done()` method if it has one.
+    Protocol {
+        next: Rc<dyn Callable>,
+        done: Option<Rc<dyn Callable>>,
+    },
+}
+
+impl Iteration {
+    pub(crate) fn next(
+        &mut self,
+        interpreter: &mut Interpreter,
+        name: &Token,
+    ) -> Result<Option<Value>, RuntimeException> {
+        match self {
+            Iteration::Values(values) => Ok(values.next()),
+            Iteration::Range(range) => Ok(range.next().map(Value::Integer)),
+            Iteration::Protocol { next, done } => {
+                let value = interpreter.call_function(next.clone(), &[], name.line)?;
+                let finished = match done {
+                    Some(done) => {
+                        let done = interpreter.call_function(done.clone(), &[], name.line)?;
+                        is_truthy(&done.to_value_with_info(name.line, &name.lexeme)?)
+                    }
+                    None => is_nil(&value),
+                };
+                match value {
+                    _ if finished => Ok(None),
+                    AloxObject::Value(value) => Ok(Some(value)),
+                    other => Err(not_iterable(name, &other)),
+                }
+            }
+        }
+    }
+}
+
+fn not_iterable(name: &Token, iterable: &AloxObject) -> RuntimeException {
+    RuntimeException::NotIterable {
+        kind: iterable.type_name().to_string(),
+        lexeme: name.lexeme.clone(),
+        line: name.line,
     }
 }
 
@@ -59,26 +845,181 @@ impl StmtVisitor<Result<(), RuntimeException>> for Interpreter {
     }
 
     fn visit_print(&mut self, print: &Print) -> Result<(), RuntimeException> {
-        let value = self.interpret_expr(&print.expression)?.to_value()?;
-        println!("{}", self.printer.print(&Expr::Literal(Literal { value })));
+        let shown = match self.interpret_expr(&print.expression)? {
+            AloxObject::Module(module) => module.to_string(),
+            object => {
+                let value = object.to_value()?;
+                self.stringify(value)?
+            }
+        };
+        println!("{}", shown);
         Ok(())
     }
 
     fn visit_var(&mut self, var: &Var) -> Result<(), RuntimeException> {
+        self.global_env.borrow().check_redeclaration(&var.name)?;
         let val = var.initializer.as_ref();
         if let Some(val) = val {
-            let val = self
-                .interpret_expr(val)?
-                .to_value_with_info(var.name.line, &var.name.lexeme)?;
-            self.global_env
-                .borrow_mut()
-                .define(&var.name.lexeme, Some(AloxObject::Value(val)));
+            let val = self.interpret_expr(val)?;
+            if var.constant {
+                self.global_env
+                    .borrow_mut()
+                    .define_constant(&var.name.lexeme, val);
+            } else {
+                self.global_env
+                    .borrow_mut()
+                    .define(&var.name.lexeme, Some(val));
+            }
         } else {
             self.global_env.borrow_mut().define(&var.name.lexeme, None);
         }
         Ok(())
     }
 
+    fn visit_destructure(&mut self, destructure: &Destructure) -> Result<(), RuntimeException> {
+        let value = self.interpret_expr(&destructure.initializer)?;
+        for (name, value) in self.destructure(&destructure.pattern, value)? {
+            self.global_env.borrow().check_redeclaration(&name)?;
+            if destructure.constant {
+                self.global_env
+                    .borrow_mut()
+                    .define_constant(&name.lexeme, value);
+            } else {
+                self.global_env
+                    .borrow_mut()
+                    .define(&name.lexeme, Some(value));
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_class(&mut self, class: &Class) -> Result<(), RuntimeException> {
+        let line = class.name.line;
+        let superclass = match &class.superclass {
+            Some(superclass) => match self.visit_expr(superclass)? {
+                AloxObject::Class(superclass) => Some(superclass),
+                object => {
+                    return Err(RuntimeException::InvalidSuperclass {
+                        lexeme: class.name.lexeme.clone(),
+                        kind: object.type_name().to_string(),
+                        line,
+                    })
+                }
+            },
+            None => None,
+        };
+        let mut traits = vec![];
+        for expr in class.traits.iter() {
+            match self.visit_expr(expr)? {
+                AloxObject::Trait(mixin) => traits.push(mixin),
+                object => {
+                    return Err(RuntimeException::NotATrait {
+                        class: class.name.lexeme.clone(),
+                        kind: object.type_name().to_string(),
+                        line,
+                    })
+                }
+            }
+        }
+        // methods of a subclass see `super` in an environment between theirs and the class's
+        let environment = match &superclass {
+            Some(superclass) => {
+                let mut environment = Environment::with_enclosing(self.global_env.clone());
+                environment.define("super", Some(AloxObject::Class(superclass.clone())));
+                Rc::new(RefCell::new(environment))
+            }
+            None => self.global_env.clone(),
+        };
+        let functions = |methods: &[Function]| {
+            methods
+                .iter()
+                .map(|method| {
+                    let function = AloxFunction::new(method.clone(), environment.clone());
+                    (method.name.lexeme.clone(), function)
+                })
+                .collect()
+        };
+        let mut methods = functions(&class.methods);
+        mix_in(&class.name.lexeme, &mut methods, &traits, line)?;
+        let class_object = AloxClass::new(
+            class.name.lexeme.clone(),
+            superclass,
+            methods,
+            functions(&class.class_methods),
+            functions(&class.getters),
+            functions(&class.setters),
+        );
+        self.global_env.borrow().check_redeclaration(&class.name)?;
+        self.global_env.borrow_mut().define(
+            &class.name.lexeme,
+            Some(AloxObject::Class(Rc::new(class_object))),
+        );
+        Ok(())
+    }
+
+    fn visit_trait_(&mut self, trait_: &Trait_) -> Result<(), RuntimeException> {
+        let methods = trait_
+            .methods
+            .iter()
+            .map(|method| {
+                let function = AloxFunction::new(method.clone(), self.global_env.clone());
+                (method.name.lexeme.clone(), function)
+            })
+            .collect();
+        let mixin = AloxTrait {
+            name: trait_.name.lexeme.clone(),
+            methods,
+        };
+        self.global_env.borrow().check_redeclaration(&trait_.name)?;
+        self.global_env
+            .borrow_mut()
+            .define(&trait_.name.lexeme, Some(AloxObject::Trait(Rc::new(mixin))));
+        Ok(())
+    }
+
+    fn visit_enum_(&mut self, enum_: &Enum_) -> Result<(), RuntimeException> {
+        let enum_type = EnumType::new(enum_);
+        let mut environment = self.global_env.borrow_mut();
+        for variant in enum_.variants.iter() {
+            environment.check_redeclaration(&variant.name)?;
+        }
+        environment.check_redeclaration(&enum_.name)?;
+        for (name, object) in enum_type.variants.iter() {
+            environment.define(name, Some(object.clone()));
+        }
+        environment.define(
+            &enum_.name.lexeme,
+            Some(AloxObject::Enum(Rc::new(enum_type))),
+        );
+        Ok(())
+    }
+
+    fn visit_match_stmt(&mut self, match_stmt: &MatchStmt) -> Result<(), RuntimeException> {
+        let (body, environment) =
+            self.select_arm(&match_stmt.keyword, &match_stmt.subject, &match_stmt.arms)?;
+        self.with_environment(Rc::new(RefCell::new(environment)), |interpreter| {
+            interpreter.visit_stmt(body)
+        })
+    }
+
+    fn visit_switch(&mut self, switch: &Switch) -> Result<(), RuntimeException> {
+        match self.select_case(switch)? {
+            Some(body) => self.visit_stmt(body),
+            None => Ok(()),
+        }
+    }
+
+    fn visit_defer(&mut self, defer: &Defer) -> Result<(), RuntimeException> {
+        match self.defers.last_mut() {
+            Some(deferred) => {
+                deferred.push(defer.expression.clone());
+                Ok(())
+            }
+            // the resolver rejects `defer` outside of blocks
+            None => Err(RuntimeException::ControlFlowError),
+        }
+    }
+
     fn visit_block(&mut self, block: &Block) -> Result<(), RuntimeException> {
         let new_env = Environment::with_enclosing(self.global_env.clone());
         self.interpret_block(&block.statements, new_env)
@@ -101,16 +1042,60 @@ impl StmtVisitor<Result<(), RuntimeException>> for Interpreter {
         Ok(())
     }
 
+    fn visit_for_in(&mut self, for_in: &ForIn) -> Result<(), RuntimeException> {
+        let iterable = self.visit_expr(&for_in.iterable)?;
+        let mut iteration = self.iteration(&for_in.name, iterable)?;
+        while let Some(value) = iteration.next(self, &for_in.name)? {
+            self.for_in_iteration(for_in, value)?;
+        }
+        Ok(())
+    }
+
+    /// Defines the function, then rebinds its name to the result of passing it through
+    /// its decorators, the one closest to `fun` first.
     fn visit_function(&mut self, function: &Function) -> Result<(), RuntimeException> {
         let alox_function = AloxFunction::new(function.clone(), self.global_env.clone());
-        self.global_env.borrow_mut().define(
-            &function.name.lexeme,
-            Some(AloxObject::Function(Rc::new(alox_function))),
-        );
+        let name = &function.name;
+        let mut decorated = AloxObject::Function(Rc::new(alox_function));
+        self.global_env.borrow().check_redeclaration(name)?;
+        self.global_env
+            .borrow_mut()
+            .define(&name.lexeme, Some(decorated.clone()));
+        if function.decorators.is_empty() {
+            return Ok(());
+        }
+
+        let mut decorators = vec![];
+        for decorator in function.decorators.iter() {
+            let decorator = self.visit_expr(decorator)?.to_callable().ok_or_else(|| {
+                RuntimeException::ExpectedFunction {
+                    line: name.line,
+                    lexeme: "@".to_string(),
+                }
+            })?;
+            decorators.push(decorator);
+        }
+        for decorator in decorators.into_iter().rev() {
+            decorated = self.call_function(decorator, &[decorated], name.line)?;
+        }
+        self.global_env
+            .borrow_mut()
+            .define(&name.lexeme, Some(decorated));
         Ok(())
     }
 
     fn visit_return_(&mut self, return_: &Return_) -> Result<(), RuntimeException> {
+        // deferred expressions must run after the returned call, so it can't be left to the caller
+        let pending_defers = self.defers.iter().any(|deferred| !deferred.is_empty());
+        let tail_call = return_.tail_call && !pending_defers;
+        if let (true, Some(Expr::Call(call))) = (tail_call, &return_.value) {
+            let (function, arguments) = self.evaluate_call(call)?;
+            return Err(RuntimeException::TailCall {
+                function,
+                arguments,
+                line: call.paren.line,
+            });
+        }
         let val = if let Some(val) = &return_.value {
             self.visit_expr(val)?
         } else {
@@ -119,119 +1104,98 @@ impl StmtVisitor<Result<(), RuntimeException>> for Interpreter {
 
         Err(RuntimeException::Return { obj: val })
     }
-}
 
-impl ExprVisitor<AloxObjResult> for Interpreter {
-    fn visit_binary(&mut self, binary: &Binary) -> AloxObjResult {
-        let left = self
-            .visit_expr(&binary.left)?
-            .to_value_with_info(binary.operator.line, &binary.operator.lexeme)?;
-        let right = self
-            .visit_expr(&binary.right)?
-            .to_value_with_info(binary.operator.line, &binary.operator.lexeme)?;
+    /// A generator runs its own `yield` statements (see `generators::resume`),
+    /// so reaching one here means it was not suspended where it should have been.
+    fn visit_yield_(&mut self, _yield: &Yield_) -> Result<(), RuntimeException> {
+        Err(RuntimeException::ControlFlowError)
+    }
 
-        match binary.operator._type {
-            TokenType::Minus => {
-                if let (Value::Number(x), Value::Number(y)) = (left, right) {
-                    Ok(AloxObject::Value(Value::Number(x - y)))
-                } else {
-                    Err(RuntimeException::InvalidOperand {
-                        lexeme: binary.operator.lexeme.clone(),
-                        expected: "Number".to_string(),
-                        line: binary.operator.line,
-                    })
-                }
-            }
-            TokenType::Slash => {
-                if let (Value::Number(x), Value::Number(y)) = (left, right) {
-                    Ok(AloxObject::Value(Value::Number(x / y)))
-                } else {
-                    Err(RuntimeException::InvalidOperand {
-                        lexeme: binary.operator.lexeme.clone(),
-                        expected: "Number".to_string(),
-                        line: binary.operator.line,
-                    })
-                }
-            }
-            TokenType::Star => {
-                if let (Value::Number(x), Value::Number(y)) = (left, right) {
-                    Ok(AloxObject::Value(Value::Number(x * y)))
-                } else {
-                    Err(RuntimeException::InvalidOperand {
-                        lexeme: binary.operator.lexeme.clone(),
-                        expected: "Number".to_string(),
-                        line: binary.operator.line,
-                    })
-                }
-            }
-            TokenType::Plus => match (left, right) {
-                (Value::Number(x), Value::Number(y)) => Ok(AloxObject::Value(Value::Number(x + y))),
-                (Value::String(x), Value::String(y)) => {
-                    Ok(AloxObject::Value(Value::String(format!("{}{}", x, y))))
-                }
-                (Value::String(x), Value::Number(y)) => {
-                    Ok(AloxObject::Value(Value::String(format!("{}{}", x, y))))
-                }
-                (Value::Number(x), Value::String(y)) => {
-                    Ok(AloxObject::Value(Value::String(format!("{}{}", x, y))))
-                }
-                _ => Err(RuntimeException::InvalidOperand {
-                    lexeme: binary.operator.lexeme.clone(),
-                    expected: "Numbers, Strings".to_string(),
-                    line: binary.operator.line,
-                }),
-            },
-            TokenType::Greater => {
-                if let (Value::Number(x), Value::Number(y)) = (left, right) {
-                    Ok(AloxObject::Value(Value::Bool(x > y)))
-                } else {
-                    Err(RuntimeException::InvalidOperand {
-                        lexeme: binary.operator.lexeme.clone(),
-                        expected: "Number".to_string(),
-                        line: binary.operator.line,
-                    })
-                }
-            }
-            TokenType::GreaterEqual => {
-                if let (Value::Number(x), Value::Number(y)) = (left, right) {
-                    Ok(AloxObject::Value(Value::Bool(x >= y)))
-                } else {
-                    Err(RuntimeException::InvalidOperand {
-                        lexeme: binary.operator.lexeme.clone(),
-                        expected: "Number".to_string(),
-                        line: binary.operator.line,
-                    })
-                }
-            }
-            TokenType::Less => {
-                if let (Value::Number(x), Value::Number(y)) = (left, right) {
-                    Ok(AloxObject::Value(Value::Bool(x < y)))
-                } else {
-                    Err(RuntimeException::InvalidOperand {
-                        lexeme: binary.operator.lexeme.clone(),
-                        expected: "Number".to_string(),
-                        line: binary.operator.line,
-                    })
-                }
+    fn visit_import(&mut self, import: &Import) -> Result<(), RuntimeException> {
+        let module = self.import_module(import)?;
+        for name in import.alias.iter().chain(import.names.iter()) {
+            self.global_env.borrow().check_redeclaration(name)?;
+        }
+        if let Some(alias) = &import.alias {
+            self.global_env
+                .borrow_mut()
+                .define_constant(&alias.lexeme, AloxObject::Module(module.clone()));
+        }
+        for name in import.names.iter() {
+            let value = module.environment.borrow().get(name)?;
+            if let Some(value) = value.as_ref() {
+                self.global_env
+                    .borrow_mut()
+                    .define_constant(&name.lexeme, value.clone());
             }
-            TokenType::LessEqual => {
-                if let (Value::Number(x), Value::Number(y)) = (left, right) {
-                    Ok(AloxObject::Value(Value::Bool(x <= y)))
-                } else {
-                    Err(RuntimeException::InvalidOperand {
-                        lexeme: binary.operator.lexeme.clone(),
-                        expected: "Number".to_string(),
-                        line: binary.operator.line,
-                    })
+        }
+        Ok(())
+    }
+
+    fn visit_throw(&mut self, throw: &Throw) -> Result<(), RuntimeException> {
+        let value = self
+            .visit_expr(&throw.value)?
+            .to_value_with_info(throw.keyword.line, &throw.keyword.lexeme)?;
+        Err(RuntimeException::Throw {
+            value,
+            line: throw.keyword.line,
+        })
+    }
+
+    fn visit_try_(&mut self, try_: &Try_) -> Result<(), RuntimeException> {
+        let mut result = self.interpret_block(
+            &try_.body,
+            Environment::with_enclosing(self.global_env.clone()),
+        );
+
+        if let Some(catch_name) = &try_.catch_name {
+            match result {
+                Err(err) if !err.is_return() => {
+                    let mut environment = Environment::with_enclosing(self.global_env.clone());
+                    environment.define(
+                        &catch_name.lexeme,
+                        Some(AloxObject::Value(caught_value(err))),
+                    );
+                    result = self.interpret_block(&try_.catch_body, environment);
                 }
+                _ => {}
             }
-            TokenType::BangEqual => Ok(AloxObject::Value(Value::Bool(left != right))),
-            TokenType::EqualEqual => Ok(AloxObject::Value(Value::Bool(left == right))),
-            _ => Err(RuntimeException::InvalidOperator {
-                lexeme: binary.operator.lexeme.clone(),
-                expression: Expr::Binary(binary.clone()),
-                line: binary.operator.line,
-            }),
+        }
+
+        self.interpret_block(
+            &try_.finally_body,
+            Environment::with_enclosing(self.global_env.clone()),
+        )?;
+        result
+    }
+}
+
+impl ExprVisitor<AloxObjResult> for Interpreter {
+    fn visit_binary(&mut self, binary: &Binary) -> AloxObjResult {
+        let left = self
+            .visit_expr(&binary.left)?
+            .to_value_with_info(binary.operator.line, &binary.operator.lexeme)?;
+        let right = self
+            .visit_expr(&binary.right)?
+            .to_value_with_info(binary.operator.line, &binary.operator.lexeme)?;
+
+        self.binary(&binary.operator, left, right, || {
+            Expr::Binary(binary.clone())
+        })
+    }
+
+    fn visit_get(&mut self, get: &Get) -> AloxObjResult {
+        let object = self.visit_expr(&get.object)?;
+        if get.optional && is_nil(&object) {
+            return Err(RuntimeException::ShortCircuit);
+        }
+        self.property(object, &get.name)
+    }
+
+    fn visit_optional_chain(&mut self, optional_chain: &OptionalChain) -> AloxObjResult {
+        match self.visit_expr(&optional_chain.expression) {
+            Err(RuntimeException::ShortCircuit) => Ok(AloxObject::Value(Value::Nil(Nil))),
+            result => result,
         }
     }
 
@@ -247,9 +1211,21 @@ impl ExprVisitor<AloxObjResult> for Interpreter {
         let right = self
             .visit_expr(&unary.right)?
             .to_value_with_info(unary.operator.line, &unary.operator.lexeme)?;
+        if let (TokenType::Minus, Value::Instance(instance)) = (&unary.operator._type, &right) {
+            if let Some(result) = self.overload(instance, "__neg", &[], unary.operator.line)? {
+                return Ok(result);
+            }
+        }
         match unary.operator._type {
             TokenType::Minus => {
-                if let Value::Number(num) = right {
+                if let Value::Integer(num) = right {
+                    num.checked_neg()
+                        .map(|num| AloxObject::Value(Value::Integer(num)))
+                        .ok_or_else(|| RuntimeException::IntegerOverflow {
+                            lexeme: unary.operator.lexeme.clone(),
+                            line: unary.operator.line,
+                        })
+                } else if let Value::Number(num) = right {
                     Ok(AloxObject::Value(Value::Number(-num)))
                 } else {
                     Err(RuntimeException::InvalidOperand {
@@ -262,7 +1238,7 @@ impl ExprVisitor<AloxObjResult> for Interpreter {
             TokenType::Bang => Ok(AloxObject::Value(Value::Bool(!is_truthy(&right)))),
             _ => Err(RuntimeException::InvalidOperator {
                 lexeme: unary.operator.lexeme.clone(),
-                expression: Expr::Unary(unary.clone()),
+                expression: Box::new(Expr::Unary(unary.clone())),
                 line: unary.operator.line,
             }),
         }
@@ -275,16 +1251,33 @@ impl ExprVisitor<AloxObjResult> for Interpreter {
     }
 
     fn visit_assign(&mut self, assign: &Assign) -> AloxObjResult {
-        let val = self
-            .visit_expr(&assign.value)?
-            .to_value_with_info(assign.name.line, &assign.name.lexeme)?;
+        let val = self.visit_expr(&assign.value)?;
         self.global_env
             .borrow_mut()
-            .assign(&assign.name, Some(AloxObject::Value(val.clone())))?;
-        Ok(AloxObject::Value(val))
+            .assign(&assign.name, Some(val.clone()))?;
+        Ok(val)
+    }
+
+    fn visit_destructure_assign(
+        &mut self,
+        destructure_assign: &DestructureAssign,
+    ) -> AloxObjResult {
+        let value = self.visit_expr(&destructure_assign.value)?;
+        for (name, part) in self.destructure(&destructure_assign.pattern, value.clone())? {
+            self.global_env.borrow_mut().assign(&name, Some(part))?;
+        }
+        Ok(value)
     }
 
     fn visit_logical(&mut self, logical: &Logical) -> AloxObjResult {
+        if logical.operator._type == TokenType::QuestionQuestion {
+            let left = self.visit_expr(&logical.left)?;
+            if !is_nil(&left) {
+                return Ok(left);
+            }
+            return self.visit_expr(&logical.right);
+        }
+
         let left = self
             .visit_expr(&logical.left)?
             .to_value_with_info(logical.operator.line, &logical.operator.lexeme)?;
@@ -300,25 +1293,1997 @@ impl ExprVisitor<AloxObjResult> for Interpreter {
         self.visit_expr(&logical.right)
     }
 
+    fn visit_compound_assign(&mut self, compound_assign: &CompoundAssign) -> AloxObjResult {
+        let operator = &compound_assign.operator;
+        let arithmetic = Token::new(
+            match operator._type {
+                TokenType::PlusEqual => TokenType::Plus,
+                TokenType::MinusEqual => TokenType::Minus,
+                TokenType::StarEqual => TokenType::Star,
+                _ => TokenType::Slash,
+            },
+            &operator.lexeme,
+            operator.line,
+        );
+        let (_, updated) =
+            self.update_target(&compound_assign.target, operator, |interpreter, current| {
+                let value = interpreter
+                    .visit_expr(&compound_assign.value)?
+                    .to_value_with_info(operator.line, &operator.lexeme)?;
+                interpreter
+                    .binary(&arithmetic, current, value, || {
+                        Expr::CompoundAssign(compound_assign.clone())
+                    })?
+                    .to_value()
+            })?;
+        Ok(AloxObject::Value(updated))
+    }
+
+    fn visit_increment(&mut self, increment: &Increment) -> AloxObjResult {
+        let operator = &increment.operator;
+        let step = Token::new(
+            if operator._type == TokenType::PlusPlus {
+                TokenType::Plus
+            } else {
+                TokenType::Minus
+            },
+            &operator.lexeme,
+            operator.line,
+        );
+        let (previous, updated) =
+            self.update_target(&increment.target, operator, |_, current| {
+                arithmetic(&step, current, Value::Integer(1))
+            })?;
+        Ok(AloxObject::Value(if increment.prefix {
+            updated
+        } else {
+            previous
+        }))
+    }
+
+    fn visit_conditional(&mut self, conditional: &Conditional) -> AloxObjResult {
+        let condition = self.visit_expr(&conditional.condition)?.to_value()?;
+        if is_truthy(&condition) {
+            self.visit_expr(&conditional.then_branch)
+        } else {
+            self.visit_expr(&conditional.else_branch)
+        }
+    }
+
     fn visit_call(&mut self, call: &Call) -> AloxObjResult {
-        let function = self.visit_expr(&call.callee)?.to_function(call)?;
-        let mut arguments = vec![];
-        for arg in call.arguments.iter() {
-            arguments.push(self.visit_expr(arg)?);
+        let (function, arguments) = self.evaluate_call(call)?;
+        self.call_function(function, &arguments, call.paren.line)
+    }
+
+    fn visit_list(&mut self, list: &List) -> AloxObjResult {
+        let mut elements = vec![];
+        for element in list.elements.iter() {
+            elements.push(
+                self.visit_expr(element)?
+                    .to_value_with_info(list.bracket.line, &list.bracket.lexeme)?,
+            );
         }
+        Ok(AloxObject::Value(Value::List(Rc::new(RefCell::new(
+            elements,
+        )))))
+    }
 
-        if function.needs_mut() {
-            function.call_mut(self, &arguments)
-        } else {
-            function.call(&self, &arguments)
+    fn visit_map(&mut self, map: &Map) -> AloxObjResult {
+        let mut entries: Vec<(Value, Value)> = vec![];
+        for (key, value) in map.keys.iter().zip(map.values.iter()) {
+            let key = self
+                .visit_expr(key)?
+                .to_value_with_info(map.brace.line, &map.brace.lexeme)?;
+            let value = self
+                .visit_expr(value)?
+                .to_value_with_info(map.brace.line, &map.brace.lexeme)?;
+            match entries
+                .iter_mut()
+                .find(|(existing, _)| values_equal(existing, &key))
+            {
+                Some(entry) => entry.1 = value,
+                None => entries.push((key, value)),
+            }
+        }
+        Ok(AloxObject::Value(Value::Map(Rc::new(RefCell::new(
+            entries,
+        )))))
+    }
+
+    fn visit_set(&mut self, set: &Set) -> AloxObjResult {
+        let object = self.visit_expr(&set.object)?;
+        let value = self.visit_expr(&set.value)?;
+        self.set_property(object, &set.name, value.clone())?;
+        Ok(value)
+    }
+
+    /// `super.name` reads a getter or method of the superclass, bound to `this`.
+    fn visit_super_(&mut self, super_: &Super_) -> AloxObjResult {
+        let this = Token::new(TokenType::This, "this", super_.keyword.line);
+        let superclass = self.global_env.borrow().get(&super_.keyword)?;
+        let instance = self.global_env.borrow().get(&this)?;
+        let (superclass, instance) = match (superclass.as_ref(), instance.as_ref()) {
+            (
+                Some(AloxObject::Class(superclass)),
+                Some(AloxObject::Value(Value::Instance(instance))),
+            ) => (superclass.clone(), instance.clone()),
+            _ => return Err(RuntimeException::EnvironmentError),
+        };
+        let name = &super_.method;
+        if let Some(getter) = superclass.find_getter(&name.lexeme) {
+            let getter = Rc::new(getter.bind(instance));
+            return self.call_function(getter, &[], name.line);
+        }
+        match superclass.find_method(&name.lexeme) {
+            Some(method) => Ok(AloxObject::Function(Rc::new(method.bind(instance)))),
+            None => Err(RuntimeException::UndefinedProperty {
+                lexeme: name.lexeme.clone(),
+                line: name.line,
+            }),
+        }
+    }
+
+    fn visit_this(&mut self, this: &This) -> AloxObjResult {
+        let this = self.global_env.borrow().get(&this.keyword)?;
+        Ok(this.as_ref().clone().unwrap())
+    }
+
+    fn visit_index(&mut self, index: &Index) -> AloxObjResult {
+        let bracket = &index.bracket;
+        let object = self
+            .visit_expr(&index.object)?
+            .to_value_with_info(bracket.line, &bracket.lexeme)?;
+        let position = self
+            .visit_expr(&index.index)?
+            .to_value_with_info(bracket.line, &bracket.lexeme)?;
+        self.index(object, position, bracket)
+    }
+
+    fn visit_set_index(&mut self, set_index: &SetIndex) -> AloxObjResult {
+        let bracket = &set_index.bracket;
+        let object = self
+            .visit_expr(&set_index.object)?
+            .to_value_with_info(bracket.line, &bracket.lexeme)?;
+        let position = self
+            .visit_expr(&set_index.index)?
+            .to_value_with_info(bracket.line, &bracket.lexeme)?;
+        let value = self
+            .visit_expr(&set_index.value)?
+            .to_value_with_info(bracket.line, &bracket.lexeme)?;
+        self.set_index(object, position, value.clone(), bracket)?;
+        Ok(AloxObject::Value(value))
+    }
+
+    fn visit_match_(&mut self, match_: &Match_) -> AloxObjResult {
+        let (body, environment) =
+            self.select_arm(&match_.keyword, &match_.subject, &match_.arms)?;
+        self.with_environment(Rc::new(RefCell::new(environment)), |interpreter| {
+            interpreter.visit_expr(body)
+        })
+    }
+
+    fn visit_range(&mut self, range: &Range) -> AloxObjResult {
+        let operator = &range.operator;
+        let start = self
+            .visit_expr(&range.start)?
+            .to_value_with_info(operator.line, &operator.lexeme)?;
+        let end = self
+            .visit_expr(&range.end)?
+            .to_value_with_info(operator.line, &operator.lexeme)?;
+        match (start, end) {
+            (Value::Integer(start), Value::Integer(end)) => {
+                let inclusive = operator._type == TokenType::DotDotEqual;
+                Ok(AloxObject::Value(Value::Range(RangeValue::new(
+                    start, end, inclusive,
+                ))))
+            }
+            _ => Err(RuntimeException::InvalidOperand {
+                lexeme: operator.lexeme.clone(),
+                expected: "integers".to_string(),
+                line: operator.line,
+            }),
+        }
+    }
+}
+
+/// Applies a binary `operator` to two already evaluated operands.
+///
+/// Integers stay integers under `+`, `-`, `*`, `~/`, `%` and `**` with a non-negative exponent,
+/// and raise `IntegerOverflow` instead of wrapping. Any other combination of numbers is
+/// promoted to floats, and `/` always divides as floats.
+///
+/// Float `/` follows IEEE 754, so dividing by zero yields `inf` or `NaN`. Integer division `~/`
+/// and modulo `%` floor towards negative infinity (the result of `%` takes the sign of the
+/// divisor, so `a == (a ~/ b) * b + a % b`) and raise `DivisionByZero` for a zero divisor.
+fn binary_operation(
+    operator: &Token,
+    left: Value,
+    right: Value,
+    expression: impl FnOnce() -> Expr,
+) -> AloxObjResult {
+    let value = match operator._type {
+        TokenType::Minus
+        | TokenType::Slash
+        | TokenType::TildeSlash
+        | TokenType::Percent
+        | TokenType::StarStar
+        | TokenType::Star => arithmetic(operator, left, right)?,
+        TokenType::Plus => match (left, right) {
+            (Value::String(x), Value::String(y)) => Value::String(format!("{}{}", x, y)),
+            (Value::String(x), y) if is_number(&y) => Value::String(format!("{}{}", x, y)),
+            (x, Value::String(y)) if is_number(&x) => Value::String(format!("{}{}", x, y)),
+            (x, y) if is_number(&x) && is_number(&y) => arithmetic(operator, x, y)?,
+            _ => {
+                return Err(RuntimeException::InvalidOperand {
+                    lexeme: operator.lexeme.clone(),
+                    expected: "Numbers, Strings".to_string(),
+                    line: operator.line,
+                })
+            }
+        },
+        TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => {
+            let ordering = match (&left, &right) {
+                (Value::Integer(x), Value::Integer(y)) => x.partial_cmp(y),
+                _ => match (as_float(&left), as_float(&right)) {
+                    (Some(x), Some(y)) => x.partial_cmp(&y),
+                    _ => {
+                        return Err(RuntimeException::InvalidOperand {
+                            lexeme: operator.lexeme.clone(),
+                            expected: "Number".to_string(),
+                            line: operator.line,
+                        })
+                    }
+                },
+            };
+            Value::Bool(match ordering {
+                Some(ordering) => match operator._type {
+                    TokenType::Greater => ordering.is_gt(),
+                    TokenType::GreaterEqual => ordering.is_ge(),
+                    TokenType::Less => ordering.is_lt(),
+                    _ => ordering.is_le(),
+                },
+                // comparisons involving NaN are always false
+                None => false,
+            })
+        }
+        TokenType::BangEqual => Value::Bool(!values_equal(&left, &right)),
+        TokenType::EqualEqual => Value::Bool(values_equal(&left, &right)),
+        _ => {
+            return Err(RuntimeException::InvalidOperator {
+                lexeme: operator.lexeme.clone(),
+                expression: Box::new(expression()),
+                line: operator.line,
+            })
+        }
+    };
+    Ok(AloxObject::Value(value))
+}
+
+/// The method an instance on the left of `operator` can declare to overload it.
+fn operator_method(operator: &Token) -> Option<&'static str> {
+    Some(match operator._type {
+        TokenType::Plus => "__add",
+        TokenType::Minus => "__sub",
+        TokenType::Star => "__mul",
+        TokenType::Slash => "__div",
+        TokenType::TildeSlash => "__floordiv",
+        TokenType::Percent => "__mod",
+        TokenType::StarStar => "__pow",
+        TokenType::EqualEqual | TokenType::BangEqual => "__eq",
+        TokenType::Less => "__lt",
+        TokenType::LessEqual => "__le",
+        TokenType::Greater => "__gt",
+        TokenType::GreaterEqual => "__ge",
+        _ => return None,
+    })
+}
+
+/// `object[index]` for lists and strings, indexed by position or sliced by a range,
+/// and maps, indexed by key. Negative positions count back from the end.
+fn index_value(object: Value, index: Value, bracket: &Token) -> ValueResult {
+    match (&object, &index) {
+        (Value::List(list), Value::Integer(position)) => {
+            let list = list.borrow();
+            let position = element_position(*position, list.len(), bracket)?;
+            Ok(list[position].clone())
+        }
+        (Value::List(list), Value::Range(range)) => {
+            let list = list.borrow();
+            let slice = slice_bounds(range, list.len(), bracket)?;
+            Ok(Value::List(Rc::new(RefCell::new(list[slice].to_vec()))))
+        }
+        (Value::String(string), Value::Integer(position)) => {
+            let position = element_position(*position, string.chars().count(), bracket)?;
+            let char = string.chars().nth(position).unwrap_or_default();
+            Ok(Value::String(char.to_string()))
+        }
+        (Value::String(string), Value::Range(range)) => {
+            let slice = slice_bounds(range, string.chars().count(), bracket)?;
+            Ok(Value::String(
+                string
+                    .chars()
+                    .skip(slice.start)
+                    .take(slice.end - slice.start)
+                    .collect(),
+            ))
+        }
+        (Value::Map(map), key) => Ok(map
+            .borrow()
+            .iter()
+            .find(|(entry, _)| values_equal(entry, key))
+            .map_or(Value::Nil(Nil), |(_, value)| value.clone())),
+        _ => Err(RuntimeException::NotIndexable {
+            kind: AloxObject::Value(object).type_name().to_string(),
+            index: AloxObject::Value(index).type_name().to_string(),
+            line: bracket.line,
+        }),
+    }
+}
+
+/// Turns `index`, which counts from the end when negative, into an offset no
+/// greater than `limit`.
+fn offset(
+    index: i64,
+    length: usize,
+    limit: usize,
+    bracket: &Token,
+) -> Result<usize, RuntimeException> {
+    let from_start = if index < 0 {
+        i64::try_from(length)
+            .ok()
+            .and_then(|length| length.checked_add(index))
+    } else {
+        Some(index)
+    };
+    from_start
+        .and_then(|offset| usize::try_from(offset).ok())
+        .filter(|offset| *offset <= limit)
+        .ok_or(RuntimeException::IndexOutOfRange {
+            index,
+            length,
+            line: bracket.line,
+        })
+}
+
+/// The position of the element at `index`, which must exist.
+fn element_position(index: i64, length: usize, bracket: &Token) -> Result<usize, RuntimeException> {
+    match length.checked_sub(1) {
+        Some(last) => offset(index, length, last, bracket),
+        None => Err(RuntimeException::IndexOutOfRange {
+            index,
+            length,
+            line: bracket.line,
+        }),
+    }
+}
+
+/// The positions a range slices out of a list or string of `length` elements.
+/// A range that ends before it starts slices out nothing.
+fn slice_bounds(
+    range: &RangeValue,
+    length: usize,
+    bracket: &Token,
+) -> Result<ops::Range<usize>, RuntimeException> {
+    let start = offset(range.start, length, length, bracket)?;
+    let end = if range.inclusive {
+        element_position(range.end, length, bracket)? + 1
+    } else {
+        offset(range.end, length, length, bracket)?
+    };
+    Ok(start..end.max(start))
+}
+
+fn arithmetic(operator: &Token, left: Value, right: Value) -> ValueResult {
+    if let (Value::Integer(x), Value::Integer(y)) = (&left, &right) {
+        return integer_arithmetic(operator, *x, *y);
+    }
+    let (x, y) = match (as_float(&left), as_float(&right)) {
+        (Some(x), Some(y)) => (x, y),
+        _ => {
+            return Err(RuntimeException::InvalidOperand {
+                lexeme: operator.lexeme.clone(),
+                expected: "Number".to_string(),
+                line: operator.line,
+            })
         }
+    };
+    let division_by_zero = || RuntimeException::DivisionByZero {
+        lexeme: operator.lexeme.clone(),
+        line: operator.line,
+    };
+    Ok(Value::Number(match operator._type {
+        TokenType::Plus => x + y,
+        TokenType::Minus => x - y,
+        TokenType::Star => x * y,
+        TokenType::Slash => x / y,
+        TokenType::TildeSlash if y == 0.0 => return Err(division_by_zero()),
+        TokenType::TildeSlash => (x / y).floor(),
+        TokenType::Percent if y == 0.0 => return Err(division_by_zero()),
+        TokenType::Percent => x - y * (x / y).floor(),
+        _ => x.powf(y),
+    }))
+}
+
+fn integer_arithmetic(operator: &Token, x: i64, y: i64) -> ValueResult {
+    let result = match operator._type {
+        TokenType::Plus => x.checked_add(y),
+        TokenType::Minus => x.checked_sub(y),
+        TokenType::Star => x.checked_mul(y),
+        TokenType::Slash => return Ok(Value::Number(x as f64 / y as f64)),
+        TokenType::TildeSlash | TokenType::Percent if y == 0 => {
+            return Err(RuntimeException::DivisionByZero {
+                lexeme: operator.lexeme.clone(),
+                line: operator.line,
+            })
+        }
+        TokenType::TildeSlash => x.checked_div(y).map(|quotient| {
+            if x % y != 0 && (x < 0) != (y < 0) {
+                quotient - 1
+            } else {
+                quotient
+            }
+        }),
+        TokenType::Percent => {
+            let remainder = x.wrapping_rem(y);
+            Some(if remainder != 0 && (remainder < 0) != (y < 0) {
+                remainder + y
+            } else {
+                remainder
+            })
+        }
+        _ if y < 0 => return Ok(Value::Number((x as f64).powf(y as f64))),
+        _ => u32::try_from(y).ok().and_then(|y| x.checked_pow(y)),
+    };
+    result
+        .map(Value::Integer)
+        .ok_or_else(|| RuntimeException::IntegerOverflow {
+            lexeme: operator.lexeme.clone(),
+            line: operator.line,
+        })
+}
+
+fn is_number(value: &Value) -> bool {
+    matches!(value, Value::Integer(_) | Value::Number(_))
+}
+
+fn as_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Integer(num) => Some(*num as f64),
+        Value::Number(num) => Some(*num),
+        _ => None,
+    }
+}
+
+/// Equality as used by `==`: integers and floats compare by numeric value, so `1 == 1.0`.
+fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Integer(x), Value::Number(y)) | (Value::Number(y), Value::Integer(x)) => {
+            *x as f64 == *y
+        }
+        _ => left == right,
+    }
+}
+
+/// The value a `catch` clause binds: thrown values are caught as they are, while
+/// interpreter errors become an `ErrorValue` carrying their message and line.
+pub(crate) fn caught_value(err: RuntimeException) -> Value {
+    match err {
+        RuntimeException::Throw { value, .. } => value,
+        err => Value::Error(ErrorValue::new(err.to_string(), err.line())),
     }
 }
 
-fn is_truthy(literal: &Value) -> bool {
+pub(crate) fn is_truthy(literal: &Value) -> bool {
     match literal {
         Value::Nil(_) => false,
         Value::Bool(boolean) => *boolean,
         _ => true,
     }
 }
+
+/// Errors that carry a `return` out of a function rather than report a failure.
+fn is_control_flow(err: &RuntimeException) -> bool {
+    matches!(
+        err,
+        RuntimeException::Return { .. } | RuntimeException::TailCall { .. }
+    )
+}
+
+fn is_nil(object: &AloxObject) -> bool {
+    matches!(object, AloxObject::Value(Value::Nil(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interpreter;
+    use crate::{
+        ast::{AloxObject, Nil, Value},
+        error::{ParserError, RuntimeException},
+        parser::Parser,
+        resolver::Resolver,
+        scanner::Scanner,
+        token::{Token, TokenType},
+    };
+
+    fn run(source: &str) -> Result<Interpreter, RuntimeException> {
+        let mut scanner = Scanner::new(source);
+        let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+        let statements = parser.parse().expect("test program should parse");
+        Resolver::new()
+            .resolve(&statements)
+            .expect("test program should resolve");
+        let mut interpreter = Interpreter::new();
+        interpreter.interpret(&statements)?;
+        Ok(interpreter)
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> Value {
+        let token = Token::new(TokenType::Identifier(name.to_string()), name, 0);
+        match interpreter
+            .global_env
+            .borrow()
+            .get(&token)
+            .unwrap()
+            .as_ref()
+        {
+            Some(AloxObject::Value(value)) => value.clone(),
+            other => panic!("expected a value for '{}', found {:?}", name, other),
+        }
+    }
+
+    fn eval(expression: &str) -> Value {
+        let interpreter = run(&format!("var result = {};", expression)).unwrap();
+        global(&interpreter, "result")
+    }
+
+    #[test]
+    fn modulo_and_integer_division_floor() {
+        assert_eq!(eval("7 % 3"), Value::Integer(1));
+        assert_eq!(eval("-7 % 3"), Value::Integer(2));
+        assert_eq!(eval("7 % -3"), Value::Integer(-2));
+        assert_eq!(eval("7 ~/ 2"), Value::Integer(3));
+        assert_eq!(eval("-7 ~/ 2"), Value::Integer(-4));
+        assert_eq!(eval("7.5 ~/ 2"), Value::Number(3.0));
+    }
+
+    #[test]
+    fn power_precedence_and_associativity() {
+        assert_eq!(eval("2 ** 3 ** 2"), Value::Integer(512));
+        assert_eq!(eval("-2 ** 2"), Value::Integer(-4));
+        assert_eq!(eval("2 ** -1"), Value::Number(0.5));
+        assert_eq!(eval("2 * 3 ** 2"), Value::Integer(18));
+        assert_eq!(eval("12 / 2 / 3"), Value::Number(2.0));
+        assert_eq!(eval("1 + 7 % 4 * 2"), Value::Integer(7));
+    }
+
+    #[test]
+    fn conditional_is_right_associative() {
+        assert_eq!(eval("true ? 1 : 2"), Value::Integer(1));
+        assert_eq!(eval("nil ? 1 : 2"), Value::Integer(2));
+        assert_eq!(eval("false ? 1 : true ? 2 : 3"), Value::Integer(2));
+        assert_eq!(eval("false ? 1 : false ? 2 : 3"), Value::Integer(3));
+        assert_eq!(
+            eval("1 < 2 ? \"yes\" : \"no\""),
+            Value::String("yes".to_string())
+        );
+    }
+
+    #[test]
+    fn conditional_only_evaluates_chosen_branch() {
+        let interpreter = run("var hits = 0;
+            fun bump() { hits = hits + 1; return hits; }
+            var a = true ? 10 : bump();
+            var b = false ? bump() : 20;")
+        .unwrap();
+        assert_eq!(global(&interpreter, "hits"), Value::Integer(0));
+        assert_eq!(global(&interpreter, "a"), Value::Integer(10));
+        assert_eq!(global(&interpreter, "b"), Value::Integer(20));
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let interpreter = run("var a = 10; a += 5; a -= 3; a *= 2; a /= 4;
+            var s = \"ab\"; s += \"c\";
+            var r = (a += 1);")
+        .unwrap();
+        assert_eq!(global(&interpreter, "a"), Value::Number(7.0));
+        assert_eq!(global(&interpreter, "s"), Value::String("abc".to_string()));
+        assert_eq!(global(&interpreter, "r"), Value::Number(7.0));
+    }
+
+    #[test]
+    fn prefix_and_postfix_increment() {
+        let interpreter = run("var i = 0;
+            var a = i++;
+            var b = ++i;
+            var c = i--;
+            var d = --i;
+            var total = 0;
+            for (var n = 0; n < 5; n++) total += n;")
+        .unwrap();
+        assert_eq!(global(&interpreter, "a"), Value::Integer(0));
+        assert_eq!(global(&interpreter, "b"), Value::Integer(2));
+        assert_eq!(global(&interpreter, "c"), Value::Integer(2));
+        assert_eq!(global(&interpreter, "d"), Value::Integer(0));
+        assert_eq!(global(&interpreter, "i"), Value::Integer(0));
+        assert_eq!(global(&interpreter, "total"), Value::Integer(10));
+    }
+
+    #[test]
+    fn compound_assignment_evaluates_target_once() {
+        let interpreter = run("var calls = 0;
+            fun next() { calls += 1; return calls; }
+            var a = 1;
+            a += next();")
+        .unwrap();
+        assert_eq!(global(&interpreter, "calls"), Value::Integer(1));
+        assert_eq!(global(&interpreter, "a"), Value::Integer(2));
+    }
+
+    #[test]
+    fn increment_rejects_non_numbers() {
+        assert!(matches!(
+            run("var s = \"a\"; s++;"),
+            Err(RuntimeException::InvalidOperand { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_assignment_target_is_a_parse_error() {
+        for source in &["1 = 2;", "(a) += 1;", "++1;", "nil--;"] {
+            let mut scanner = Scanner::new(source);
+            let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+            assert!(matches!(
+                parser.parse(),
+                Err(ParserError::InvalidAssignmentTarget { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn catch_thrown_value() {
+        let interpreter = run("var caught;
+            try {
+                throw \"oops\";
+                caught = \"unreachable\";
+            } catch (e) {
+                caught = e;
+            }")
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "caught"),
+            Value::String("oops".to_string())
+        );
+    }
+
+    #[test]
+    fn catch_runtime_error_as_value() {
+        let interpreter = run("var message; var line;
+            try {
+                var x = 1;
+                x = x + true;
+            } catch (e) {
+                message = e.message;
+                line = e.line;
+            }")
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "message"),
+            Value::String("[line 4] '+' operands must be Numbers, Strings.".to_string())
+        );
+        assert_eq!(global(&interpreter, "line"), Value::Integer(4));
+    }
+
+    #[test]
+    fn finally_always_runs() {
+        let interpreter = run("var log = \"\";
+            try { log += \"a\"; } finally { log += \"b\"; }
+            try { throw 1; } catch (e) { log += \"c\"; } finally { log += \"d\"; }
+            fun early() {
+                try { return \"e\"; } finally { log += \"f\"; }
+            }
+            var returned = early();")
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "log"),
+            Value::String("abcdf".to_string())
+        );
+        assert_eq!(
+            global(&interpreter, "returned"),
+            Value::String("e".to_string())
+        );
+    }
+
+    #[test]
+    fn uncaught_exceptions_propagate_after_finally() {
+        let err = run("var cleaned = false;
+            try {
+                try { throw \"inner\"; } finally { cleaned = true; }
+            } catch (e) {
+                throw e + \"!\";
+            }")
+        .err()
+        .unwrap();
+        match err {
+            RuntimeException::Throw { value, line } => {
+                assert_eq!(value, Value::String("inner!".to_string()));
+                assert_eq!(line, 5);
+            }
+            other => panic!("expected a thrown value, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn constants_hold_any_object() {
+        let interpreter = run("const limit = 3;
+            fun twice(n) { return n * 2; }
+            const double = twice;
+            const result = double(limit);")
+        .unwrap();
+        assert_eq!(global(&interpreter, "result"), Value::Integer(6));
+    }
+
+    #[test]
+    fn assigning_global_constant_fails_at_runtime() {
+        for source in &[
+            "const a = 1; a = 2;",
+            "const a = 1; a += 2;",
+            "const a = 1; fun f() { a--; } f();",
+        ] {
+            assert!(matches!(
+                run(source),
+                Err(RuntimeException::AssignToConstant { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn redeclaring_constant_is_an_error() {
+        for source in &[
+            "const a = 1; var a = 2;",
+            "const a = 1; const a = 2;",
+            "const a = 1; var [a, b] = [2, 3];",
+            "const a = 1; fun a() {}",
+            "const a = 1; class a {}",
+            "const Some = 1; enum Option { Some(value), None }",
+        ] {
+            assert!(
+                matches!(
+                    run(source).err(),
+                    Some(RuntimeException::RedeclareConstant { line: 1, .. })
+                ),
+                "{}",
+                source
+            );
+        }
+        let interpreter = run("const a = 1; { var a = 2; a = 3; }").unwrap();
+        assert_eq!(global(&interpreter, "a"), Value::Integer(1));
+    }
+
+    #[test]
+    fn constants_must_be_initialized() {
+        let mut scanner = Scanner::new("const a;");
+        let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+        assert!(matches!(
+            parser.parse(),
+            Err(ParserError::UnexpectedToken { .. })
+        ));
+    }
+
+    #[test]
+    fn integer_and_float_promotion() {
+        assert_eq!(eval("1 + 2"), Value::Integer(3));
+        assert_eq!(eval("1 + 2.0"), Value::Number(3.0));
+        assert_eq!(eval("7 / 2"), Value::Number(3.5));
+        assert_eq!(eval("6 / 3"), Value::Number(2.0));
+        assert_eq!(eval("7.0 ~/ 2"), Value::Number(3.0));
+        assert_eq!(eval("2 ** 62"), Value::Integer(1 << 62));
+        assert_eq!(eval("1 == 1.0"), Value::Bool(true));
+        assert_eq!(eval("1 < 1.5"), Value::Bool(true));
+        assert_eq!(eval("\"n=\" + 3"), Value::String("n=3".to_string()));
+        assert_eq!(eval("\"n=\" + 3.0"), Value::String("n=3.0".to_string()));
+    }
+
+    #[test]
+    fn large_integers_keep_precision() {
+        assert_eq!(
+            eval("9007199254740993 + 1"),
+            Value::Integer(9_007_199_254_740_994)
+        );
+    }
+
+    #[test]
+    fn integer_overflow_is_an_error() {
+        for source in &[
+            "9223372036854775807 + 1;",
+            "-9223372036854775807 - 2;",
+            "4294967296 * 4294967296;",
+            "2 ** 64;",
+            "var i = 9223372036854775807; i++;",
+        ] {
+            assert!(matches!(
+                run(source),
+                Err(RuntimeException::IntegerOverflow { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn int_and_float_conversions() {
+        assert_eq!(eval("int(3.9)"), Value::Integer(3));
+        assert_eq!(eval("int(-3.9)"), Value::Integer(-3));
+        assert_eq!(eval("int(\" 42 \")"), Value::Integer(42));
+        assert_eq!(eval("int(\"4.2\")"), Value::Integer(4));
+        assert_eq!(eval("float(3)"), Value::Number(3.0));
+        assert_eq!(eval("float(\"2.5\")"), Value::Number(2.5));
+        for source in &["int(\"abc\");", "int(1 / 0);", "float(nil);"] {
+            assert!(matches!(
+                run(source),
+                Err(RuntimeException::NativeError { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn integers_and_floats_print_differently() {
+        assert_eq!(Value::Integer(3).to_string(), "3");
+        assert_eq!(Value::Number(3.0).to_string(), "3.0");
+        assert_eq!(Value::Number(0.5).to_string(), "0.5");
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(eval("1 / 0"), Value::Number(f64::INFINITY));
+        assert!(matches!(
+            run("7 % 0;"),
+            Err(RuntimeException::DivisionByZero { .. })
+        ));
+        assert!(matches!(
+            run("7 ~/ 0;"),
+            Err(RuntimeException::DivisionByZero { .. })
+        ));
+        assert!(matches!(
+            run("7.0 % 0.0;"),
+            Err(RuntimeException::DivisionByZero { .. })
+        ));
+    }
+
+    #[test]
+    fn arithmetic_operators_reject_non_numbers() {
+        for source in &["\"a\" % 2;", "2 ** true;", "nil ~/ 2;"] {
+            assert!(matches!(
+                run(source),
+                Err(RuntimeException::InvalidOperand { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn for_in_over_lists_strings_maps_and_ranges() {
+        let interpreter = run(r#"var sum = 0;
+            for (x in [1, 2, 3]) sum += x;
+            var chars = 0;
+            for (c in "héllo") chars++;
+            var keys = "";
+            for (key in {"a": 1, "b": 2, "a": 3}) keys += key;
+            var total = 0;
+            for (i in 0..2 + 3) total += i;
+            for (i in 3..1) total = nil;"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "sum"), Value::Integer(6));
+        assert_eq!(global(&interpreter, "chars"), Value::Integer(5));
+        assert_eq!(
+            global(&interpreter, "keys"),
+            Value::String("ab".to_string())
+        );
+        assert_eq!(global(&interpreter, "total"), Value::Integer(10));
+    }
+
+    #[test]
+    fn for_in_closures_capture_each_iteration() {
+        let interpreter = run("var first;
+            var last;
+            for (i in 0..3) {
+                fun get() { return i; }
+                if (i == 0) first = get;
+                last = get;
+            }
+            var a = first();
+            var b = last();")
+        .unwrap();
+        assert_eq!(global(&interpreter, "a"), Value::Integer(0));
+        assert_eq!(global(&interpreter, "b"), Value::Integer(2));
+    }
+
+    #[test]
+    fn for_in_rejects_non_iterables() {
+        assert!(matches!(
+            run("for (x in 5) print x;"),
+            Err(RuntimeException::NotIterable { .. })
+        ));
+        assert!(matches!(
+            run("for (x in 0..1.5) print x;"),
+            Err(RuntimeException::InvalidOperand { .. })
+        ));
+    }
+
+    #[test]
+    fn lists_and_maps_print_their_elements() {
+        assert_eq!(
+            eval(r#"[1, "a", [2.5], {"k": nil}]"#).to_string(),
+            r#"[1, "a", [2.5], {"k": Nil}]"#
+        );
+        assert_eq!(eval("1..4").to_string(), "1..4");
+        assert_eq!(eval("[1, 2] == [1, 2]"), Value::Bool(true));
+    }
+
+    #[test]
+    fn calls_check_arity() {
+        for (source, expected) in &[
+            (
+                "fun f(a, b) {} f(1);",
+                "[line 1] f() expects 2 arguments but got 1.",
+            ),
+            (
+                "fun f(a, b = 2) {} f(1, 2, 3);",
+                "[line 1] f() expects 1 to 2 arguments but got 3.",
+            ),
+            (
+                "fun f(a, ...rest) {} f();",
+                "[line 1] f() expects at least 1 argument but got 0.",
+            ),
+            ("int();", "[line 1] int() expects 1 argument but got 0."),
+            (
+                "class Point { init(x, y) {} }\nvar origin = Point(0);",
+                "[line 2] Point() expects 2 arguments but got 1.",
+            ),
+        ] {
+            match run(source) {
+                Err(err @ RuntimeException::ArityMismatch { .. }) => {
+                    assert_eq!(err.to_string(), *expected)
+                }
+                other => panic!(
+                    "expected an arity error for '{}', got {:?}",
+                    source,
+                    other.err()
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn default_parameters_see_earlier_parameters() {
+        let interpreter = run("fun f(a, b = a * 2, c = b + 1) { return [a, b, c]; }
+            var one = f(1);
+            var two = f(1, 5);
+            var three = f(1, 5, 0);")
+        .unwrap();
+        assert_eq!(global(&interpreter, "one").to_string(), "[1, 2, 3]");
+        assert_eq!(global(&interpreter, "two").to_string(), "[1, 5, 6]");
+        assert_eq!(global(&interpreter, "three").to_string(), "[1, 5, 0]");
+    }
+
+    #[test]
+    fn type_annotations_do_not_change_evaluation() {
+        let interpreter = run(
+            "fun scale(n: Number, by: Integer = 2): Number { return n * by; }
+            var x: Number = scale(1.5);
+            const label: String = \"x = \" + x;",
+        )
+        .unwrap();
+        assert_eq!(global(&interpreter, "x"), Value::Number(3.0));
+        assert_eq!(
+            global(&interpreter, "label"),
+            Value::String("x = 3.0".to_string())
+        );
+    }
+
+    #[test]
+    fn rest_parameter_collects_extra_arguments() {
+        let interpreter = run("fun sum(first, ...rest) {
+                var total = first;
+                for (n in rest) total += n;
+                return total;
+            }
+            var single = sum(1);
+            var many = sum(1, 2, 3, 4);
+            fun collect(...all) { return all; }
+            var none = collect();")
+        .unwrap();
+        assert_eq!(global(&interpreter, "single"), Value::Integer(1));
+        assert_eq!(global(&interpreter, "many"), Value::Integer(10));
+        assert_eq!(global(&interpreter, "none").to_string(), "[]");
+    }
+
+    #[test]
+    fn rest_parameter_rejects_functions() {
+        let err = run("fun apply(f, ...args) { return f; }
+            var applied = apply(clock, 1);
+            apply(1, clock);")
+        .err();
+        assert!(matches!(
+            err,
+            Some(RuntimeException::NotCollectable { ref kind, ref lexeme, line: 1 })
+                if kind == "function" && lexeme == "args"
+        ));
+    }
+
+    #[test]
+    fn malformed_parameter_lists_are_parse_errors() {
+        for source in &["fun f(a = 1, b) {}", "fun f(...rest, a) {}"] {
+            let mut scanner = Scanner::new(source);
+            let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+            assert!(matches!(
+                parser.parse(),
+                Err(ParserError::MissingDefault { .. }) | Err(ParserError::RestNotLast { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn destructuring_declarations() {
+        let interpreter = run(r#"fun pair() { return [1, "two"]; }
+            var [a, b] = pair();
+            var {x, y} = {"y": 20, "x": 10};
+            const [only] = [[3]];"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "a"), Value::Integer(1));
+        assert_eq!(global(&interpreter, "b"), Value::String("two".to_string()));
+        assert_eq!(global(&interpreter, "x"), Value::Integer(10));
+        assert_eq!(global(&interpreter, "y"), Value::Integer(20));
+        assert_eq!(global(&interpreter, "only").to_string(), "[3]");
+    }
+
+    #[test]
+    fn destructuring_assignment_swaps() {
+        let interpreter = run("var a = 1;
+            var b = 2;
+            var result = [a, b] = [b, a];")
+        .unwrap();
+        assert_eq!(global(&interpreter, "a"), Value::Integer(2));
+        assert_eq!(global(&interpreter, "b"), Value::Integer(1));
+        assert_eq!(global(&interpreter, "result").to_string(), "[2, 1]");
+    }
+
+    #[test]
+    fn destructuring_reads_instance_properties() {
+        let interpreter = run("class Point {
+                init(x, y) { this.x = x; this.y = y; }
+                sum { return this.x + this.y; }
+            }
+            var {x, y, sum} = Point(1, 2);")
+        .unwrap();
+        assert_eq!(global(&interpreter, "x"), Value::Integer(1));
+        assert_eq!(global(&interpreter, "y"), Value::Integer(2));
+        assert_eq!(global(&interpreter, "sum"), Value::Integer(3));
+
+        let err = run("class Point { init() { this.x = 1; } }
+            var {x, z} = Point();")
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "[line 2] Undefined property 'z'.");
+    }
+
+    #[test]
+    fn destructuring_shape_mismatches() {
+        for (source, expected) in &[
+            (
+                "var [a, b] = [1];",
+                "[line 1] Cannot destructure: '[a, b]' expects 2 elements, found 1.",
+            ),
+            (
+                "var [a] = 1;",
+                "[line 1] Cannot destructure: '[a]' expects a list, found integer.",
+            ),
+            (
+                r#"var {x} = {"y": 1};"#,
+                "[line 1] Cannot destructure: key 'x' is missing.",
+            ),
+            (
+                "var {x} = [1];",
+                "[line 1] Cannot destructure: '{x}' expects a map, found list.",
+            ),
+        ] {
+            match run(source) {
+                Err(err @ RuntimeException::DestructureMismatch { .. }) => {
+                    assert_eq!(err.to_string(), *expected)
+                }
+                other => panic!(
+                    "expected a destructuring error for '{}', got {:?}",
+                    source,
+                    other.err()
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn generators_run_lazily() {
+        let interpreter = run(r#"var log = "";
+            fun numbers(n) {
+                for (i in 0..n) {
+                    log += "p" + i;
+                    yield i;
+                }
+                log += "done";
+            }
+            var gen = numbers(2);
+            var started = log;
+            var first = gen.next();
+            var after_first = log;
+            var second = gen.next();
+            var third = gen.next();
+            var fourth = gen.next();"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "started"),
+            Value::String("".to_string())
+        );
+        assert_eq!(global(&interpreter, "first"), Value::Integer(0));
+        assert_eq!(
+            global(&interpreter, "after_first"),
+            Value::String("p0".to_string())
+        );
+        assert_eq!(global(&interpreter, "second"), Value::Integer(1));
+        assert_eq!(global(&interpreter, "third"), Value::Nil(Nil));
+        assert_eq!(global(&interpreter, "fourth"), Value::Nil(Nil));
+        assert_eq!(
+            global(&interpreter, "log"),
+            Value::String("p0p1done".to_string())
+        );
+    }
+
+    #[test]
+    fn generators_are_iterable() {
+        let interpreter = run("fun evens(limit) {
+                var n = 0;
+                while (n < limit) {
+                    if (n % 2 == 0) yield n;
+                    n++;
+                }
+            }
+            fun squares(source) {
+                for (x in source) yield x * x;
+            }
+            var sum = 0;
+            for (x in squares(evens(7))) sum += x;")
+        .unwrap();
+        assert_eq!(global(&interpreter, "sum"), Value::Integer(56));
+    }
+
+    #[test]
+    fn stored_generators_are_iterable() {
+        let interpreter = run("fun countdown(n) {
+                while (n > 0) yield n--;
+            }
+            var numbers = countdown(3);
+            var order = \"\";
+            for (n in numbers) order += n;
+            var after = numbers.next();")
+        .unwrap();
+        assert_eq!(global(&interpreter, "order"), Value::String("321".into()));
+        assert_eq!(global(&interpreter, "after"), Value::Nil(Nil));
+    }
+
+    #[test]
+    fn generators_can_yield_nil() {
+        let interpreter = run(r#"fun readings() {
+                yield 1;
+                yield nil;
+                yield;
+                yield 2;
+            }
+            var seen = "";
+            for (reading in readings()) seen += reading ?? "-";
+            var it = readings();
+            var states = "";
+            var value = it.next();
+            while (!it.done()) {
+                states += value ?? "-";
+                value = it.next();
+            }
+            var after = it.next();
+            var finished = it.done();"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "seen"), Value::String("1--2".into()));
+        assert_eq!(global(&interpreter, "states"), Value::String("1--2".into()));
+        assert_eq!(global(&interpreter, "after"), Value::Nil(Nil));
+        assert_eq!(global(&interpreter, "finished"), Value::Bool(true));
+    }
+
+    #[test]
+    fn iterators_with_done_can_return_nil() {
+        let interpreter = run(r#"class Blanks {
+                init(count) { this.left = count; }
+                next() { this.left -= 1; return nil; }
+                done() { return this.left < 0; }
+            }
+            var count = 0;
+            for (blank in Blanks(3)) count += 1;"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "count"), Value::Integer(3));
+    }
+
+    #[test]
+    fn generators_run_catch_and_finally() {
+        let interpreter = run(r#"var log = "";
+            fun guarded() {
+                try {
+                    yield 1;
+                    throw "boom";
+                } catch (e) {
+                    yield e;
+                } finally {
+                    log += "finally";
+                }
+                yield 2;
+                try {
+                    return;
+                } finally {
+                    log += " again";
+                }
+                yield 3;
+            }
+            var items = "";
+            for (x in guarded()) items += x + " ";"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "items"),
+            Value::String("1 boom 2 ".to_string())
+        );
+        assert_eq!(
+            global(&interpreter, "log"),
+            Value::String("finally again".to_string())
+        );
+    }
+
+    #[test]
+    fn generator_errors_propagate_to_the_caller() {
+        let err = run(r#"fun failing() {
+                yield 1;
+                throw "broken";
+            }
+            var gen = failing();
+            gen.next();
+            gen.next();"#)
+        .err()
+        .unwrap();
+        assert!(matches!(err, RuntimeException::Throw { line: 3, .. }));
+    }
+
+    #[test]
+    fn tail_calls_run_in_constant_stack() {
+        let interpreter = run("fun count(n, total) {
+                if (n == 0) return total;
+                return count(n - 1, total + 1);
+            }
+            var counted = count(1000000, 0);
+            fun is_even(n) {
+                if (n == 0) return true;
+                return is_odd(n - 1);
+            }
+            fun is_odd(n) {
+                if (n == 0) return false;
+                return is_even(n - 1);
+            }
+            var even = is_even(100001);")
+        .unwrap();
+        assert_eq!(global(&interpreter, "counted"), Value::Integer(1000000));
+        assert_eq!(global(&interpreter, "even"), Value::Bool(false));
+    }
+
+    #[test]
+    fn returns_inside_try_are_not_tail_calls() {
+        let interpreter = run(r#"var log = "";
+            fun inner() {
+                log += "inner ";
+                return 1;
+            }
+            fun outer() {
+                try {
+                    return inner();
+                } finally {
+                    log += "finally";
+                }
+            }
+            var result = outer();
+            fun finish() {
+                log += " finish";
+            }
+            fun gen() {
+                yield 1;
+                return finish();
+            }
+            var items = 0;
+            for (x in gen()) items += x;"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "log"),
+            Value::String("inner finally finish".to_string())
+        );
+        assert_eq!(global(&interpreter, "result"), Value::Integer(1));
+        assert_eq!(global(&interpreter, "items"), Value::Integer(1));
+    }
+
+    #[test]
+    fn match_selects_the_first_matching_arm() {
+        let interpreter = run(r#"enum Shape { Circle(r), Rect(w, h), Empty }
+            fun area(shape) {
+                return match (shape) {
+                    Circle(r) => 3 * r * r,
+                    Rect(w, 0) => 0,
+                    Rect(w, h) => w * h,
+                    Empty => 0,
+                };
+            }
+            var areas = [area(Circle(2)), area(Shape.Rect(2, 5)), area(Rect(2, 0)), area(Empty)];
+            var shown = [Rect(1, "x"), Empty];
+            fun describe(value) {
+                return match (value) {
+                    0 => "zero",
+                    -1 => "minus one",
+                    "a" => "letter",
+                    nil => "nothing",
+                    other => "other " + other,
+                };
+            }
+            var described = [describe(0), describe(-1), describe("a"), describe(nil), describe(2)];"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "areas").to_string(), "[12, 10, 0, 0]");
+        assert_eq!(
+            global(&interpreter, "shown").to_string(),
+            r#"[Rect(1, "x"), Empty]"#
+        );
+        assert_eq!(
+            global(&interpreter, "described").to_string(),
+            r#"["zero", "minus one", "letter", "nothing", "other 2"]"#
+        );
+    }
+
+    #[test]
+    fn match_statements_run_the_arm_body() {
+        let interpreter = run(r#"enum Option { Some(value), None }
+            var log = "";
+            for (option in [Some(Some(1)), Some(None), None]) {
+                match (option) {
+                    Some(Some(x)) => log += "nested " + x + ";";
+                    Some(_) => {
+                        var inner = "some";
+                        log += inner + ";";
+                    }
+                    None => log += "none;";
+                }
+            }
+            fun values() {
+                for (option in [Some(1), None, Some(2)]) {
+                    match (option) {
+                        Some(x) => yield x;
+                        None => {}
+                    }
+                }
+            }
+            var sum = 0;
+            for (x in values()) sum += x;"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "log"),
+            Value::String("nested 1;some;none;".to_string())
+        );
+        assert_eq!(global(&interpreter, "sum"), Value::Integer(3));
+    }
+
+    #[test]
+    fn match_without_a_matching_arm_is_an_error() {
+        let err = run("enum Shape { Circle(r), Rect(w, h) }
+            var shape = Rect(1, 2);
+            match (shape) { Circle(r) => print r; }")
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "[line 3] No match arm matches Rect(1, 2).");
+
+        let err = run("match (1) { Circel(r) => print r; }").err().unwrap();
+        assert!(matches!(err, RuntimeException::NotAVariant { .. }));
+
+        let err = run("enum Shape { Circle(r) }
+            match (Circle(1)) { Circle(r, extra) => print r; }")
+        .err()
+        .unwrap();
+        assert!(matches!(err, RuntimeException::DestructureMismatch { .. }));
+    }
+
+    #[test]
+    fn instances_hold_fields_and_bound_methods() {
+        let interpreter = run("class Counter {
+                init(start) {
+                    this.count = start;
+                }
+                increment() {
+                    this.count += 1;
+                    return this;
+                }
+            }
+            var counter = Counter(1);
+            counter.increment().increment();
+            var increment = counter.increment;
+            increment();
+            var count = counter.count;
+            counter.count = 10;
+            counter.count++;
+            var assigned = counter.count;")
+        .unwrap();
+        assert_eq!(global(&interpreter, "count"), Value::Integer(4));
+        assert_eq!(global(&interpreter, "assigned"), Value::Integer(11));
+
+        let err = run("class Point { init(x, y) {} } Point(1);")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "[line 1] Point() expects 2 arguments but got 1."
+        );
+        let err = run("var list = [1]; list.size = 2;").err().unwrap();
+        assert!(matches!(
+            err,
+            RuntimeException::InvalidPropertyTarget { .. }
+        ));
+    }
+
+    #[test]
+    fn index_targets_can_be_assigned() {
+        let interpreter = run(r#"var list = [1, 2, 3];
+            list[0] = 10;
+            list[-1] += 5;
+            list[1]++;
+            var map = {"a": 1};
+            map["a"] *= 3;
+            map["b"] = "new";
+            var reads = 0;
+            fun position() {
+                reads++;
+                return 0;
+            }
+            list[position()] -= 1;
+            var assigned = list[2] = 7;
+            class Grid {
+                init() { this.cells = {}; }
+                __index(key) { return this.cells[key] ?? 0; }
+                __setindex(key, value) { this.cells[key] = value; }
+            }
+            var grid = Grid();
+            grid["x"] += 2;
+            grid["x"]++;
+            var elements = "" + list[0] + list[1] + list[2];
+            var a = map["a"];
+            var b = map["b"];
+            var cell = grid["x"];"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "elements"),
+            Value::String("937".into())
+        );
+        assert_eq!(global(&interpreter, "a"), Value::Integer(3));
+        assert_eq!(global(&interpreter, "b"), Value::String("new".into()));
+        assert_eq!(global(&interpreter, "reads"), Value::Integer(1));
+        assert_eq!(global(&interpreter, "assigned"), Value::Integer(7));
+        assert_eq!(global(&interpreter, "cell"), Value::Integer(3));
+
+        for source in &["var s = \"ab\"; s[0] = \"c\";", "var l = [1]; l[0..1] = 2;"] {
+            assert!(matches!(
+                run(source).err(),
+                Some(RuntimeException::NotIndexAssignable { .. })
+            ));
+        }
+        assert!(matches!(
+            run("var l = [1]; l[1] = 2;").err(),
+            Some(RuntimeException::IndexOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn index_assignment_supports_map_memoization() {
+        let interpreter = run(r#"fun memoize(f) {
+                var cache = {};
+                fun memo(n) {
+                    if (cache[n] == nil) cache[n] = f(n);
+                    return cache[n];
+                }
+                return memo;
+            }
+            var calls = 0;
+            @memoize
+            fun fib(n) {
+                calls += 1;
+                if (n < 2) return n;
+                return fib(n - 1) + fib(n - 2);
+            }
+            var result = fib(40);"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "result"), Value::Integer(102334155));
+        assert_eq!(global(&interpreter, "calls"), Value::Integer(41));
+    }
+
+    #[test]
+    fn instances_overload_operators() {
+        let interpreter = run(r#"class Vector {
+                init(x, y) {
+                    this.x = x;
+                    this.y = y;
+                }
+                __add(other) { return Vector(this.x + other.x, this.y + other.y); }
+                __mul(factor) { return Vector(this.x * factor, this.y * factor); }
+                __neg() { return Vector(-this.x, -this.y); }
+                __eq(other) { return this.x == other.x and this.y == other.y; }
+                __lt(other) { return this.x * this.x + this.y * this.y < other.x * other.x + other.y * other.y; }
+                __index(i) { return [this.x, this.y][i]; }
+                __str() { return "(" + this.x + ", " + this.y + ")"; }
+            }
+            var sum = Vector(1, 2) + Vector(3, 4) * 2;
+            var total = Vector(0, 0);
+            total += sum;
+            var shown = "sum = " + -total;
+            var equal = sum == Vector(7, 10);
+            var unequal = sum != Vector(7, 10);
+            var shorter = Vector(1, 1) < sum;
+            var second = sum[1];"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "shown"),
+            Value::String("sum = (-7, -10)".to_string())
+        );
+        assert_eq!(global(&interpreter, "equal"), Value::Bool(true));
+        assert_eq!(global(&interpreter, "unequal"), Value::Bool(false));
+        assert_eq!(global(&interpreter, "shorter"), Value::Bool(true));
+        assert_eq!(global(&interpreter, "second"), Value::Integer(10));
+    }
+
+    #[test]
+    fn instances_without_overloads_use_the_builtin_operators() {
+        let interpreter = run("class Empty {}
+            var a = Empty();
+            var b = Empty();
+            var same = a == a;
+            var different = a == b;
+            var listed = [a];")
+        .unwrap();
+        assert_eq!(global(&interpreter, "same"), Value::Bool(true));
+        assert_eq!(global(&interpreter, "different"), Value::Bool(false));
+        assert_eq!(
+            global(&interpreter, "listed").to_string(),
+            "[<Empty instance>]"
+        );
+        let err = run("class Empty {} var sum = Empty() + 1;").err().unwrap();
+        assert!(matches!(err, RuntimeException::InvalidOperand { .. }));
+    }
+
+    #[test]
+    fn lists_strings_and_maps_can_be_indexed() {
+        let interpreter = run(r#"var list = [1, 2, 3];
+            var string = "héllo";
+            var map = {"a": 1};
+            var indexed = [list[2], string[1], map["a"], map["b"]];"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "indexed").to_string(),
+            r#"[3, "é", 1, Nil]"#
+        );
+        let err = run("var list = [1]; list[1];").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "[line 1] Index 1 is out of range for length 1."
+        );
+        let err = run("var n = 1; n[0];").err().unwrap();
+        assert!(matches!(err, RuntimeException::NotIndexable { .. }));
+    }
+
+    #[test]
+    fn ranges_slice_lists_and_strings() {
+        let interpreter = run(r#"var list = [1, 2, 3, 4, 5];
+            var string = "héllo";
+            var slices = [list[1..3], list[1..=3], list[-2..5], list[0..-1], list[3..1]];
+            var substrings = [string[1..3], string[-3..=-1], string[2..2]];
+            var last = [list[-1], string[-5]];"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "slices").to_string(),
+            "[[2, 3], [2, 3, 4], [4, 5], [1, 2, 3, 4], []]"
+        );
+        assert_eq!(
+            global(&interpreter, "substrings").to_string(),
+            r#"["él", "llo", ""]"#
+        );
+        assert_eq!(global(&interpreter, "last").to_string(), r#"[5, "h"]"#);
+
+        let err = run("var list = [1, 2]; list[0..3];").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "[line 1] Index 3 is out of range for length 2."
+        );
+        let err = run("var list = [1, 2]; list[-3];").err().unwrap();
+        assert!(matches!(err, RuntimeException::IndexOutOfRange { .. }));
+        let err = run("var list = [1, 2]; list[0..1.5];").err().unwrap();
+        assert!(matches!(err, RuntimeException::InvalidOperand { .. }));
+    }
+
+    #[test]
+    fn inclusive_ranges_include_their_end() {
+        let interpreter = run("var total = 0;
+            for (i in 1..=4) total += i;
+            var empty = 0;
+            for (i in 3..3) empty += 1;
+            var shown = [1..=3, 1..3];")
+        .unwrap();
+        assert_eq!(global(&interpreter, "total"), Value::Integer(10));
+        assert_eq!(global(&interpreter, "empty"), Value::Integer(0));
+        assert_eq!(global(&interpreter, "shown").to_string(), "[1..=3, 1..3]");
+    }
+
+    #[test]
+    fn nil_coalescing_only_evaluates_the_right_side_for_nil() {
+        let interpreter = run("var calls = 0;
+            fun fallback() { calls += 1; return 2; }
+            var missing = nil ?? fallback();
+            var present = 1 ?? fallback();
+            var falsy = false ?? fallback();
+            var chained = nil ?? nil ?? 3;")
+        .unwrap();
+        assert_eq!(global(&interpreter, "missing"), Value::Integer(2));
+        assert_eq!(global(&interpreter, "present"), Value::Integer(1));
+        assert_eq!(global(&interpreter, "falsy"), Value::Bool(false));
+        assert_eq!(global(&interpreter, "chained"), Value::Integer(3));
+        assert_eq!(global(&interpreter, "calls"), Value::Integer(1));
+    }
+
+    #[test]
+    fn optional_chaining_short_circuits_the_whole_chain_on_nil() {
+        let interpreter = run("class Box {
+                init(value) { this.value = value; }
+                get() { return this.value; }
+            }
+            var calls = 0;
+            fun count() { calls += 1; return 1; }
+            var full = Box(Box(5));
+            var empty = nil;
+            var f = nil;
+            var field = full?.value.value;
+            var method = full?.get()?.get();
+            var missing = empty?.value.value;
+            var skipped = empty?.get(count());
+            var called = f?.(count());
+            var defaulted = empty?.value ?? 0;")
+        .unwrap();
+        assert_eq!(global(&interpreter, "field"), Value::Integer(5));
+        assert_eq!(global(&interpreter, "method"), Value::Integer(5));
+        assert_eq!(global(&interpreter, "missing"), Value::Nil(Nil));
+        assert_eq!(global(&interpreter, "skipped"), Value::Nil(Nil));
+        assert_eq!(global(&interpreter, "called"), Value::Nil(Nil));
+        assert_eq!(global(&interpreter, "defaulted"), Value::Integer(0));
+        assert_eq!(global(&interpreter, "calls"), Value::Integer(0));
+
+        let err = run("var empty = nil; empty.value;").err().unwrap();
+        assert!(matches!(err, RuntimeException::UndefinedProperty { .. }));
+    }
+
+    #[test]
+    fn pipelines_pass_the_left_value_as_the_first_argument() {
+        let interpreter = run("fun double(n) { return n * 2; }
+            fun add(a, b) { return a + b; }
+            class Scaler {
+                init(factor) { this.factor = factor; }
+                scale(n) { return n * this.factor; }
+            }
+            var scaler = Scaler(10);
+            var piped = 3 |> double |> add(1);
+            var method = 2 + 1 |> scaler.scale;
+            var missing = nil;
+            var optional = (1 |> missing?.scale()) ?? 0;
+            var chosen = true ? 1 |> double : 0;")
+        .unwrap();
+        assert_eq!(global(&interpreter, "piped"), Value::Integer(7));
+        assert_eq!(global(&interpreter, "method"), Value::Integer(30));
+        assert_eq!(global(&interpreter, "optional"), Value::Integer(0));
+        assert_eq!(global(&interpreter, "chosen"), Value::Integer(2));
+
+        let err = run("fun one(a) { return a; } 1 |> one(2);").err().unwrap();
+        assert!(matches!(err, RuntimeException::ArityMismatch { .. }));
+    }
+
+    #[test]
+    fn switch_runs_only_the_first_equal_case() {
+        let interpreter = run(r#"fun describe(x) {
+                var result = "none";
+                switch (x) {
+                    case 1, 2:
+                        result = "small";
+                    case 1.0, "one":
+                        result = "unreachable";
+                    case "x":
+                        var found = "x";
+                        result = found;
+                    default:
+                        result = "other";
+                }
+                return result;
+            }
+            var results = [describe(1), describe(2.0), describe("one"), describe("x"), describe(nil)];
+            var untouched = 0;
+            switch (3) { case 1: untouched = 1; }"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "results").to_string(),
+            r#"["small", "small", "unreachable", "x", "other"]"#
+        );
+        assert_eq!(global(&interpreter, "untouched"), Value::Integer(0));
+    }
+
+    #[test]
+    fn switch_compares_instances_with_their_eq_method() {
+        let interpreter = run(r#"class Id {
+                init(n) { this.n = n; }
+                __eq(other) { return this.n == other; }
+            }
+            var evaluated = 0;
+            fun value(n) { evaluated += 1; return n; }
+            var matched = "none";
+            switch (Id(2)) {
+                case value(1): matched = "one";
+                case value(2): matched = "two";
+                case value(3): matched = "three";
+            }"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "matched"), Value::String("two".into()));
+        assert_eq!(global(&interpreter, "evaluated"), Value::Integer(2));
+    }
+
+    #[test]
+    fn switch_with_two_defaults_is_a_parse_error() {
+        let mut scanner = Scanner::new("switch (1) { default: print 1; default: print 2; }");
+        let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn class_methods_are_called_on_the_class() {
+        let interpreter = run("class Math {
+                class square(n) { return n * n; }
+                class cube(n) { return n * Math.square(n); }
+            }
+            var results = [Math.square(3), Math.cube(2)];")
+        .unwrap();
+        assert_eq!(global(&interpreter, "results").to_string(), "[9, 8]");
+
+        let err = run("class Math { class square(n) { return n * n; } } Math().square(2);")
+            .err()
+            .unwrap();
+        assert!(matches!(err, RuntimeException::UndefinedProperty { .. }));
+    }
+
+    #[test]
+    fn getters_and_setters_run_on_property_access() {
+        let interpreter = run("class Temperature {
+                init(celsius) { this.celsius = celsius; }
+                fahrenheit { return this.celsius * 9 ~/ 5 + 32; }
+                set fahrenheit(value) { this.celsius = (value - 32) * 5 ~/ 9; }
+            }
+            var t = Temperature(100);
+            var boiling = t.fahrenheit;
+            t.fahrenheit = 32;
+            var freezing = t.celsius;
+            t.fahrenheit += 18;
+            var raised = t.celsius;")
+        .unwrap();
+        assert_eq!(global(&interpreter, "boiling"), Value::Integer(212));
+        assert_eq!(global(&interpreter, "freezing"), Value::Integer(0));
+        assert_eq!(global(&interpreter, "raised"), Value::Integer(10));
+
+        let err = run("class Rect {
+                init(w, h) { this.w = w; this.h = h; }
+                area { return this.w * this.h; }
+            }
+            Rect(2, 3).area = 1;")
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "[line 5] Property 'area' has a getter but no setter."
+        );
+    }
+
+    #[test]
+    fn setters_take_exactly_one_parameter() {
+        let mut scanner = Scanner::new("class A { set value(a, b) {} }");
+        let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+        assert!(matches!(
+            parser.parse(),
+            Err(ParserError::SetterArity { .. })
+        ));
+    }
+
+    #[test]
+    fn subclasses_inherit_and_call_super() {
+        let interpreter = run(r#"class Base {
+                init(name) { this.name = name; }
+                describe() { return "base " + this.name; }
+                label { return "<" + this.name + ">"; }
+                class create(name) { return Money(name, 0); }
+            }
+            class Money < Base {
+                init(name, cents) {
+                    super.init(name);
+                    this.cents = cents;
+                }
+                describe() { return super.describe() + " worth " + this.cents; }
+            }
+            var money = Base.create("coin");
+            var results = [Money("note", 5).describe(), Money("bill", 1).label, money.cents];"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "results").to_string(),
+            r#"["base note worth 5", "<bill>", 0]"#
+        );
+
+        let err = run("var Base = 1; class A < Base {}").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "[line 1] Superclass of 'A' must be a class, found integer."
+        );
+    }
+
+    #[test]
+    fn traits_mix_methods_into_classes() {
+        let interpreter = run(r#"trait Comparable {
+                less(other) { return this.compare(other) < 0; }
+                same(other) { return this.compare(other) == 0; }
+            }
+            trait Printable {
+                show() { return this.name + ": " + this.cents; }
+                same(other) { return false; }
+            }
+            class Base {
+                init(name) { this.name = name; }
+            }
+            class Money < Base with Comparable, Printable {
+                init(cents) {
+                    super.init("money");
+                    this.cents = cents;
+                }
+                compare(other) { return this.cents - other.cents; }
+                same(other) { return this.cents == other.cents; }
+            }
+            var results = [Money(1).less(Money(2)), Money(3).same(Money(3)), Money(4).show()];"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "results").to_string(),
+            r#"[true, true, "money: 4"]"#
+        );
+    }
+
+    #[test]
+    fn conflicting_trait_methods_are_an_error_at_class_creation() {
+        let err = run("trait A { greet() { return 1; } }
+            trait B { greet() { return 2; } }
+            class C with A, B {}")
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "[line 3] Class 'C' gets method 'greet' from both 'A' and 'B'; define it in the class to choose one."
+        );
+        let err = run("class A {} class B with A {}").err().unwrap();
+        assert!(matches!(err, RuntimeException::NotATrait { .. }));
+    }
+
+    #[test]
+    fn decorators_replace_the_function_they_decorate() {
+        let interpreter = run(r#"fun memoize(f) {
+                fun none(n) { return nil; }
+                var lookup = none;
+                fun memo(n) {
+                    var hit = lookup(n);
+                    if (hit != nil) return hit;
+                    var result = f(n);
+                    var previous = lookup;
+                    fun found(m) {
+                        if (m == n) return result;
+                        return previous(m);
+                    }
+                    lookup = found;
+                    return result;
+                }
+                return memo;
+            }
+            var calls = 0;
+            @memoize
+            fun fib(n) {
+                calls += 1;
+                if (n < 2) return n;
+                return fib(n - 1) + fib(n - 2);
+            }
+            var result = fib(30);"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "result"), Value::Integer(832040));
+        assert_eq!(global(&interpreter, "calls"), Value::Integer(31));
+    }
+
+    #[test]
+    fn decorators_are_applied_once_closest_first() {
+        let interpreter = run(r#"var log = "";
+            fun tag(label) {
+                log = log + label;
+                fun decorate(f) {
+                    log = log + "(" + label + ")";
+                    fun wrapped(x) { return label + f(x); }
+                    return wrapped;
+                }
+                return decorate;
+            }
+            @tag("a") @tag("b")
+            fun name(x) { return x; }
+            var first = name("!");
+            var second = name("?");"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "log"),
+            Value::String("ab(b)(a)".into())
+        );
+        assert_eq!(global(&interpreter, "first"), Value::String("ab!".into()));
+        assert_eq!(global(&interpreter, "second"), Value::String("ab?".into()));
+
+        let err = run("var n = 1; @n fun f() {}").err().unwrap();
+        assert!(matches!(err, RuntimeException::ExpectedFunction { .. }));
+    }
+
+    #[test]
+    fn deferred_expressions_run_last_first_when_block_exits() {
+        let interpreter = run(r#"var log = "";
+            {
+                defer log += "a";
+                defer log += "b";
+                log += "c";
+            }
+            for (x in [1, 2]) {
+                defer log += "d";
+                log += "e";
+            }"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "log"), Value::String("cbaeded".into()));
+    }
+
+    #[test]
+    fn deferred_expressions_run_after_return_value() {
+        let interpreter = run(r#"var log = "";
+            fun f() {
+                var local = "x";
+                defer log += local;
+                return log += "r";
+            }
+            fun g() {
+                defer log += "g";
+                return h();
+            }
+            fun h() {
+                log += "h";
+                return log;
+            }
+            var returned = f();
+            var tail = g();"#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "returned"), Value::String("r".into()));
+        assert_eq!(global(&interpreter, "tail"), Value::String("rxh".into()));
+        assert_eq!(global(&interpreter, "log"), Value::String("rxhg".into()));
+    }
+
+    #[test]
+    fn deferred_expressions_run_when_error_propagates() {
+        let interpreter = run(r#"var log = "";
+            fun fail() {
+                defer log += "cleanup";
+                throw "boom";
+            }
+            try { fail(); } catch (e) { log += " " + e; }"#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "log"),
+            Value::String("cleanup boom".into())
+        );
+    }
+}
@@ -2,18 +2,25 @@
 extern crate derive_new;
 #[macro_use]
 extern crate derive_is_enum_variant;
+use checker::Checker;
 use error::AyloxError;
 use interpreter::Interpreter;
 use parser::Parser;
+use resolver::Resolver;
 use scanner::Scanner;
-use std::fs;
+use std::{fs, path::Path};
 
 pub mod ast;
 pub mod ast_printer;
+pub mod checker;
+pub mod classes;
+pub mod enums;
 pub mod environment;
 pub mod error;
 pub mod functions;
+pub mod generators;
 pub mod interpreter;
+pub mod modules;
 pub mod native_functions;
 pub mod parser;
 pub mod prompt;
@@ -23,17 +30,30 @@ pub mod token;
 
 pub fn run_file(path: &str) -> Result<(), AyloxError> {
     let contents = fs::read_to_string(path)?;
-    run(&contents)?;
+    execute(&contents, Interpreter::with_script_path(Path::new(path)))?;
     Ok(())
 }
 
 pub fn run(contents: &str) -> Result<(), AyloxError> {
+    execute(contents, Interpreter::new())
+}
+
+fn execute(contents: &str, mut interpreter: Interpreter) -> Result<(), AyloxError> {
     let mut scanner = Scanner::new(contents);
-    let mut parser = Parser::new(scanner.scan_tokens());
+    let mut parser = Parser::new(scanner.scan_tokens()?);
     let statements = parser.parse()?;
-    let mut interpreter = Interpreter::new();
+    let mut resolver = Resolver::new();
+    resolver.resolve(&statements)?;
+    for warning in resolver.warnings() {
+        println!("Warning: {}", warning);
+    }
+    Checker::new().check(&statements)?;
 
-    if let Err(err) = interpreter.interpret(&statements) {
+    let result = interpreter.interpret(&statements);
+    for warning in interpreter.warnings() {
+        println!("Warning: {}", warning);
+    }
+    if let Err(err) = result {
         println!("Runtime Error: {}", err);
     }
     Ok(())
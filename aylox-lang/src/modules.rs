@@ -0,0 +1,56 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::environment::Environment;
+
+/// A Lox file evaluated by `import`, holding the global environment its top-level names live in.
+#[derive(new, Debug)]
+pub struct Module {
+    pub path: PathBuf,
+    pub environment: Rc<RefCell<Environment>>,
+}
+
+impl Display for Module {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<module {}>", self.path.display())
+    }
+}
+
+/// Every module imported while running a program, so each file is only evaluated once,
+/// along with the chain of modules currently being evaluated, used to detect import cycles.
+#[derive(Default)]
+pub struct ModuleCache {
+    pub loaded: HashMap<PathBuf, Rc<Module>>,
+    pub loading: Vec<PathBuf>,
+}
+
+impl ModuleCache {
+    /// Formats the import chain that leads back to `path`, e.g. `a.lox -> b.lox -> a.lox`.
+    pub fn cycle_to(&self, path: &Path) -> String {
+        let start = self
+            .loading
+            .iter()
+            .position(|loading| loading == path)
+            .unwrap_or(0);
+        self.loading[start..]
+            .iter()
+            .chain(std::iter::once(&path.to_path_buf()))
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+}
+
+/// Resolves an import path relative to the directory of the importing file,
+/// or to the working directory when the importing code did not come from a file.
+pub fn resolve_import(importer: Option<&Path>, import: &str) -> PathBuf {
+    match importer.and_then(Path::parent) {
+        Some(dir) => dir.join(import),
+        None => PathBuf::from(import),
+    }
+}
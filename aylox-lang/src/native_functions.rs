@@ -1,6 +1,6 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::{ast::*, functions::Callable, interpreter::Interpreter};
+use crate::{ast::*, error::RuntimeException, functions::Callable, interpreter::Interpreter};
 
 pub struct Clock;
 
@@ -9,6 +9,10 @@ impl Callable for Clock {
         false
     }
 
+    fn name(&self) -> &str {
+        "clock"
+    }
+
     fn arity(&self) -> usize {
         0
     }
@@ -26,3 +30,90 @@ impl Callable for Clock {
         todo!()
     }
 }
+
+pub struct Int;
+
+impl Callable for Int {
+    fn needs_mut(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        "int"
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    /// Truncates floats towards zero and parses strings such as `"42"` or `"4.2"`.
+    fn call(&self, _interpreter: &Interpreter, args: &[AloxObject]) -> AloxObjResult {
+        let error = |msg: &str| RuntimeException::NativeError {
+            function: "int".to_string(),
+            msg: msg.to_string(),
+        };
+        let float = match args.first() {
+            Some(AloxObject::Value(Value::Integer(num))) => {
+                return Ok(AloxObject::Value(Value::Integer(*num)))
+            }
+            Some(AloxObject::Value(Value::Number(num))) => *num,
+            Some(AloxObject::Value(Value::String(string))) => {
+                let string = string.trim();
+                if let Ok(num) = string.parse::<i64>() {
+                    return Ok(AloxObject::Value(Value::Integer(num)));
+                }
+                string
+                    .parse::<f64>()
+                    .map_err(|_| error(&format!("cannot convert '{}' to an integer", string)))?
+            }
+            _ => return Err(error("expects a number or a string")),
+        };
+        let truncated = float.trunc();
+        if truncated.is_finite() && truncated >= i64::MIN as f64 && truncated < i64::MAX as f64 {
+            Ok(AloxObject::Value(Value::Integer(truncated as i64)))
+        } else {
+            Err(error(&format!("cannot convert {:?} to an integer", float)))
+        }
+    }
+
+    fn call_mut(&self, _interpreter: &mut Interpreter, _args: &[AloxObject]) -> AloxObjResult {
+        unreachable!("int() is called through call")
+    }
+}
+
+pub struct Float;
+
+impl Callable for Float {
+    fn needs_mut(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        "float"
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, _interpreter: &Interpreter, args: &[AloxObject]) -> AloxObjResult {
+        let error = |msg: String| RuntimeException::NativeError {
+            function: "float".to_string(),
+            msg,
+        };
+        let float = match args.first() {
+            Some(AloxObject::Value(Value::Integer(num))) => *num as f64,
+            Some(AloxObject::Value(Value::Number(num))) => *num,
+            Some(AloxObject::Value(Value::String(string))) => string
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| error(format!("cannot convert '{}' to a float", string)))?,
+            _ => return Err(error("expects a number or a string".to_string())),
+        };
+        Ok(AloxObject::Value(Value::Number(float)))
+    }
+
+    fn call_mut(&self, _interpreter: &mut Interpreter, _args: &[AloxObject]) -> AloxObjResult {
+        unreachable!("float() is called through call")
+    }
+}
@@ -13,11 +13,20 @@ type ParseStmtsResult = Result<Vec<Stmt>, ParserError>;
 pub struct Parser<'a> {
     tokens: &'a [Token],
     current: usize,
+    /// For each function being parsed, whether its body contains a `yield`.
+    generators: Vec<bool>,
+    /// How many `try` statements of the current function enclose the parser's position.
+    tries: usize,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(tokens: &'a [Token]) -> Self {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            generators: vec![],
+            tries: 0,
+        }
     }
 
     pub fn parse(&mut self) -> ParseStmtsResult {
@@ -50,20 +59,18 @@ impl<'a> Parser<'a> {
     }
 
     fn declaration(&mut self) -> ParseStmtResult {
-        if self.token_match(&[TokenType::Var]) {
-            if let Ok(stmt) = self.var_declaration() {
-                return Ok(stmt);
-            } else {
-                self.synchronize()
-            }
-        }
-
-        if let Ok(stmt) = self.statement() {
-            Ok(stmt)
+        let result = if self.token_match(&[TokenType::Var]) {
+            self.var_declaration()
+        } else if self.token_match(&[TokenType::Const]) {
+            self.const_declaration()
         } else {
+            self.statement()
+        };
+
+        if result.is_err() {
             self.synchronize();
-            Err(ParserError::Generic)
         }
+        result
     }
 
     fn statement(&mut self) -> ParseStmtResult {
@@ -85,12 +92,164 @@ impl<'a> Parser<'a> {
         if self.token_match(&[TokenType::Fun]) {
             return self.function(FunctionKind::Function);
         }
+        if self.token_match(&[TokenType::At]) {
+            return self.decorated_function();
+        }
         if self.token_match(&[TokenType::Return]) {
             return self.return_statement();
         }
+        if self.token_match(&[TokenType::Yield]) {
+            return self.yield_statement();
+        }
+        if self.token_match(&[TokenType::Throw]) {
+            return self.throw_statement();
+        }
+        if self.token_match(&[TokenType::Defer]) {
+            return self.defer_statement();
+        }
+        if self.token_match(&[TokenType::Try]) {
+            return self.try_statement();
+        }
+        if self.token_match(&[TokenType::Class]) {
+            return self.class_declaration();
+        }
+        if self.token_match(&[TokenType::Trait]) {
+            return self.trait_declaration();
+        }
+        if self.token_match(&[TokenType::Enum]) {
+            return self.enum_declaration();
+        }
+        if self.token_match(&[TokenType::Match]) {
+            return self.match_statement();
+        }
+        if self.token_match(&[TokenType::Switch]) {
+            return self.switch_statement();
+        }
+        if self.token_match(&[TokenType::Import]) {
+            return self.import_statement();
+        }
+        if self.token_match(&[TokenType::From]) {
+            return self.import_names_statement();
+        }
         self.expression_statement()
     }
 
+    fn import_statement(&mut self) -> ParseStmtResult {
+        let keyword = self.previous().clone();
+        let path = self
+            .consume(
+                &TokenType::String(String::new()),
+                "Expected module path after 'import'",
+            )?
+            .clone();
+        self.consume(&TokenType::As, "Expected 'as' after module path")?;
+        let alias = self
+            .consume(
+                &TokenType::Identifier(String::new()),
+                "Expected module name after 'as'",
+            )?
+            .clone();
+        self.consume(&TokenType::Semicolon, "Expected ';' after import.")?;
+        Ok(Stmt::Import(Import::new(
+            keyword,
+            path,
+            Some(alias),
+            vec![],
+        )))
+    }
+
+    fn import_names_statement(&mut self) -> ParseStmtResult {
+        let keyword = self.previous().clone();
+        let path = self
+            .consume(
+                &TokenType::String(String::new()),
+                "Expected module path after 'from'",
+            )?
+            .clone();
+        self.consume(&TokenType::Import, "Expected 'import' after module path")?;
+        let mut names = vec![];
+        loop {
+            names.push(
+                self.consume(
+                    &TokenType::Identifier(String::new()),
+                    "Expected name to import",
+                )?
+                .clone(),
+            );
+            if !self.token_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&TokenType::Semicolon, "Expected ';' after import.")?;
+        Ok(Stmt::Import(Import::new(keyword, path, None, names)))
+    }
+
+    fn throw_statement(&mut self) -> ParseStmtResult {
+        let keyword = self.previous().clone();
+        let value = self.expression()?;
+        self.consume(&TokenType::Semicolon, "Expected ';' after thrown value.")?;
+        Ok(Stmt::Throw(Throw::new(keyword, value)))
+    }
+
+    /// `defer expr;` runs `expr` when the enclosing block is left, however it is left.
+    fn defer_statement(&mut self) -> ParseStmtResult {
+        let keyword = self.previous().clone();
+        let expression = self.expression()?;
+        self.consume(
+            &TokenType::Semicolon,
+            "Expected ';' after deferred expression.",
+        )?;
+        Ok(Stmt::Defer(Defer::new(keyword, expression)))
+    }
+
+    fn try_statement(&mut self) -> ParseStmtResult {
+        self.tries += 1;
+        let result = self.try_clauses();
+        self.tries -= 1;
+        result
+    }
+
+    fn try_clauses(&mut self) -> ParseStmtResult {
+        let keyword = self.previous().clone();
+        self.consume(&TokenType::LeftBrace, "Expected '{' after 'try'")?;
+        let body = self.block_statement()?;
+
+        let mut catch_name = None;
+        let mut catch_body = vec![];
+        if self.token_match(&[TokenType::Catch]) {
+            self.consume(&TokenType::LeftParen, "Expected '(' after 'catch'")?;
+            catch_name = Some(
+                self.consume(
+                    &TokenType::Identifier(String::new()),
+                    "Expected error variable name",
+                )?
+                .clone(),
+            );
+            self.consume(&TokenType::RightParen, "Expected ')' after error variable")?;
+            self.consume(&TokenType::LeftBrace, "Expected '{' before catch body")?;
+            catch_body = self.block_statement()?;
+        }
+
+        let mut finally_body = vec![];
+        if self.token_match(&[TokenType::Finally]) {
+            self.consume(&TokenType::LeftBrace, "Expected '{' after 'finally'")?;
+            finally_body = self.block_statement()?;
+        } else if catch_name.is_none() {
+            return Err(Parser::error(
+                self.peek(),
+                "Expected 'catch' or 'finally' after try block",
+            ));
+        }
+
+        Ok(Stmt::Try_(Try_::new(
+            keyword,
+            body,
+            catch_name,
+            catch_body,
+            finally_body,
+        )))
+    }
+
     fn return_statement(&mut self) -> ParseStmtResult {
         let keyword = self.previous().clone();
         let val = if !self.check(&TokenType::Semicolon) {
@@ -100,7 +259,27 @@ impl<'a> Parser<'a> {
         };
 
         self.consume(&TokenType::Semicolon, "Expected ';' after return value.")?;
-        Ok(Stmt::Return_(Return_::new(keyword, val)))
+        // a call is only in tail position if no enclosing `try` has work left after it returns
+        let tail_call =
+            matches!(val, Some(Expr::Call(_))) && !self.generators.is_empty() && self.tries == 0;
+        Ok(Stmt::Return_(Return_::new(keyword, val, tail_call)))
+    }
+
+    /// `yield value;` turns the enclosing function into a generator. The resolver
+    /// rejects a `yield` that is not inside a function.
+    fn yield_statement(&mut self) -> ParseStmtResult {
+        let keyword = self.previous().clone();
+        let value = if !self.check(&TokenType::Semicolon) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+
+        self.consume(&TokenType::Semicolon, "Expected ';' after yield value.")?;
+        if let Some(generator) = self.generators.last_mut() {
+            *generator = true;
+        }
+        Ok(Stmt::Yield_(Yield_::new(keyword, value)))
     }
 
     fn function(&mut self, kind: FunctionKind) -> ParseStmtResult {
@@ -110,13 +289,21 @@ impl<'a> Parser<'a> {
                 &format!("Expected {} name", kind),
             )?
             .clone();
-        self.consume(
-            &TokenType::LeftParen,
-            &format!("Expected '(' after {} name", kind),
-        )?;
         let mut parameters: Vec<Token> = vec![];
-
-        if !self.check(&TokenType::RightParen) {
+        let mut defaults: Vec<Expr> = vec![];
+        let mut param_types: Vec<Annotation> = vec![];
+        let mut rest = None;
+
+        // getters have no parameter list
+        let has_parameters = kind != FunctionKind::Getter;
+        if has_parameters {
+            self.consume(
+                &TokenType::LeftParen,
+                &format!("Expected '(' after {} name", kind),
+            )?;
+        }
+        // required parameters come first, then ones with defaults and finally a `...rest` list
+        if has_parameters && !self.check(&TokenType::RightParen) {
             loop {
                 if parameters.len() >= 255 {
                     let err = ParserError::FunctionParameterLength {
@@ -124,30 +311,108 @@ impl<'a> Parser<'a> {
                     };
                     println!("{}", err);
                 }
-                parameters.push(
-                    self.consume(
+                let is_rest = self.token_match(&[TokenType::DotDotDot]);
+                let parameter = self
+                    .consume(
                         &TokenType::Identifier(String::new()),
                         "Expected parameter name",
                     )?
-                    .clone(),
-                );
+                    .clone();
+                if is_rest {
+                    if !self.check(&TokenType::RightParen) {
+                        return Err(ParserError::RestNotLast {
+                            line: parameter.line,
+                            lexeme: parameter.lexeme,
+                        });
+                    }
+                    rest = Some(parameter);
+                    break;
+                }
+                param_types.push(self.annotation()?);
+                if self.token_match(&[TokenType::Equal]) {
+                    defaults.push(self.expression()?);
+                } else if !defaults.is_empty() {
+                    return Err(ParserError::MissingDefault {
+                        line: parameter.line,
+                        lexeme: parameter.lexeme,
+                    });
+                }
+                parameters.push(parameter);
                 if !self.token_match(&[TokenType::Comma]) {
                     break;
                 }
             }
         }
-        self.consume(&TokenType::RightParen, "Expected ')' after parameters")?;
+        if has_parameters {
+            self.consume(&TokenType::RightParen, "Expected ')' after parameters")?;
+        }
+        if kind == FunctionKind::Setter && (parameters.len() != 1 || rest.is_some()) {
+            return Err(ParserError::SetterArity {
+                line: name.line,
+                lexeme: name.lexeme,
+            });
+        }
+        let return_type = self.annotation()?;
 
         self.consume(
             &TokenType::LeftBrace,
             &format!("Expected '{{' before {} body", kind),
         )?;
-        let body = self.block_statement()?;
-        Ok(Stmt::Function(Function::new(name, parameters, body)))
+        self.generators.push(false);
+        let tries = mem::replace(&mut self.tries, 0);
+        let body = self.block_statement();
+        self.tries = tries;
+        let generator = self.generators.pop().unwrap_or(false);
+        Ok(Stmt::Function(Function::new(
+            name,
+            parameters,
+            defaults,
+            rest,
+            body?,
+            generator,
+            param_types,
+            return_type,
+            vec![],
+        )))
+    }
+
+    /// `@memoize @trace("fib") fun fib(n) { ... }`, after the first `@`. Each decorator
+    /// is an expression naming or calling something that takes the function and
+    /// returns what the name is bound to instead.
+    fn decorated_function(&mut self) -> ParseStmtResult {
+        let mut decorators = vec![self.call()?];
+        while self.token_match(&[TokenType::At]) {
+            decorators.push(self.call()?);
+        }
+        self.consume(&TokenType::Fun, "Expected 'fun' after decorators")?;
+        match self.function(FunctionKind::Function)? {
+            Stmt::Function(mut function) => {
+                function.decorators = decorators;
+                Ok(Stmt::Function(function))
+            }
+            statement => Ok(statement),
+        }
+    }
+
+    /// An optional `: Type` after a variable, parameter or parameter list.
+    fn annotation(&mut self) -> Result<Option<Token>, ParserError> {
+        if !self.token_match(&[TokenType::Colon]) {
+            return Ok(None);
+        }
+        let name = self
+            .consume(
+                &TokenType::Identifier(String::new()),
+                "Expected type name after ':'",
+            )?
+            .clone();
+        Ok(Some(name))
     }
 
     fn for_statement(&mut self) -> ParseStmtResult {
         self.consume(&TokenType::LeftParen, "Expected '(' after 'for'")?;
+        if self.check(&TokenType::Identifier(String::new())) && self.check_next(&TokenType::In) {
+            return self.for_in_statement();
+        }
         let initializer = if self.token_match(&[TokenType::Semicolon]) {
             None
         } else if self.token_match(&[TokenType::Var]) {
@@ -194,6 +459,17 @@ impl<'a> Parser<'a> {
         Ok(body)
     }
 
+    /// `for (name in iterable) body`, after the opening parenthesis.
+    fn for_in_statement(&mut self) -> ParseStmtResult {
+        let name = self.advance().clone();
+        self.advance();
+        let iterable = self.expression()?;
+        self.consume(&TokenType::RightParen, "Expected ')' after 'for' iterable")?;
+        let body = self.statement()?;
+
+        Ok(Stmt::ForIn(ForIn::new(name, iterable, Box::new(body))))
+    }
+
     fn while_statement(&mut self) -> ParseStmtResult {
         self.consume(&TokenType::LeftParen, "Expected '(' after 'while'")?;
         let condition = self.expression()?;
@@ -236,12 +512,16 @@ impl<'a> Parser<'a> {
     }
 
     fn var_declaration(&mut self) -> ParseStmtResult {
+        if self.token_match(&[TokenType::LeftBracket, TokenType::LeftBrace]) {
+            return self.destructuring_declaration(false);
+        }
         let name = self
             .consume(
                 &TokenType::Identifier(String::new()),
                 "Expected variable name",
             )?
             .clone();
+        let annotation = self.annotation()?;
         let mut initializer: Option<Expr> = None;
 
         if self.token_match(&[TokenType::Equal]) {
@@ -252,7 +532,95 @@ impl<'a> Parser<'a> {
             &TokenType::Semicolon,
             "Expected ';' after variable declaration.",
         )?;
-        Ok(Stmt::new_var(Var::new(name, initializer)))
+        Ok(Stmt::new_var(Var::new(
+            name,
+            initializer,
+            false,
+            annotation,
+        )))
+    }
+
+    fn const_declaration(&mut self) -> ParseStmtResult {
+        if self.token_match(&[TokenType::LeftBracket, TokenType::LeftBrace]) {
+            return self.destructuring_declaration(true);
+        }
+        let name = self
+            .consume(
+                &TokenType::Identifier(String::new()),
+                "Expected constant name",
+            )?
+            .clone();
+        let annotation = self.annotation()?;
+        self.consume(
+            &TokenType::Equal,
+            &format!("Constant '{}' must be initialized", name.lexeme),
+        )?;
+        let initializer = self.expression()?;
+
+        self.consume(
+            &TokenType::Semicolon,
+            "Expected ';' after constant declaration.",
+        )?;
+        Ok(Stmt::new_var(Var::new(
+            name,
+            Some(initializer),
+            true,
+            annotation,
+        )))
+    }
+
+    /// `var [a, b] = pair;` or `const {x, y} = point;`, after the opening bracket or brace.
+    fn destructuring_declaration(&mut self, constant: bool) -> ParseStmtResult {
+        let pattern = self.pattern()?;
+        self.consume(
+            &TokenType::Equal,
+            &format!(
+                "Destructuring declaration '{}' must be initialized",
+                pattern
+            ),
+        )?;
+        let initializer = self.expression()?;
+        self.consume(
+            &TokenType::Semicolon,
+            "Expected ';' after variable declaration.",
+        )?;
+        Ok(Stmt::Destructure(Destructure::new(
+            pattern,
+            initializer,
+            constant,
+        )))
+    }
+
+    /// The names of a `[a, b]` or `{x, y}` pattern, after the opening bracket or brace.
+    fn pattern(&mut self) -> Result<Pattern, ParserError> {
+        let open = self.previous().clone();
+        let close = if open._type == TokenType::LeftBracket {
+            TokenType::RightBracket
+        } else {
+            TokenType::RightBrace
+        };
+        let mut names = vec![];
+        while !self.check(&close) {
+            names.push(
+                self.consume(
+                    &TokenType::Identifier(String::new()),
+                    "Expected variable name in pattern",
+                )?
+                .clone(),
+            );
+            if !self.token_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&close, "Expected end of pattern")?;
+        Ok(if open._type == TokenType::LeftBracket {
+            Pattern::List {
+                bracket: open,
+                names,
+            }
+        } else {
+            Pattern::Map { brace: open, names }
+        })
     }
 
     fn print_statement(&mut self) -> ParseStmtResult {
@@ -272,7 +640,7 @@ impl<'a> Parser<'a> {
     }
 
     fn assignment(&mut self) -> ParseExprResult {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
 
         if self.token_match(&[TokenType::Equal]) {
             let equals = self.previous().clone();
@@ -281,14 +649,136 @@ impl<'a> Parser<'a> {
                 let name = var.name;
                 return Ok(Expr::Assign(Assign::new(name, Box::new(value))));
             }
-            let err = ParserError::UnexpectedToken {
+            if let Expr::Get(get) = expr {
+                return Ok(Expr::Set(Set::new(get.object, get.name, Box::new(value))));
+            }
+            if let Expr::Index(index) = expr {
+                return Ok(Expr::SetIndex(SetIndex::new(
+                    index.object,
+                    index.bracket,
+                    index.index,
+                    Box::new(value),
+                )));
+            }
+            if let Some(pattern) = Parser::list_pattern(&expr) {
+                return Ok(Expr::DestructureAssign(DestructureAssign::new(
+                    pattern,
+                    equals,
+                    Box::new(value),
+                )));
+            }
+            return Err(ParserError::InvalidAssignmentTarget {
                 lexeme: equals.lexeme,
-                msg: "Invalid assignment target".to_string(),
                 line: equals.line,
+            });
+        }
+
+        if self.token_match(&[
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+        ]) {
+            let operator = self.previous().clone();
+            let value = self.assignment()?;
+            Parser::check_assignment_target(&expr, &operator)?;
+            return Ok(Expr::CompoundAssign(CompoundAssign::new(
+                Box::new(expr),
+                operator,
+                Box::new(value),
+            )));
+        }
+        Ok(expr)
+    }
+
+    /// A list literal of plain variables, such as `[a, b]`, is a pattern on the left of `=`.
+    fn list_pattern(expr: &Expr) -> Option<Pattern> {
+        if let Expr::List(list) = expr {
+            let names = list
+                .elements
+                .iter()
+                .map(|element| match element {
+                    Expr::Variable(variable) => Some(variable.name.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<Token>>>()?;
+            return Some(Pattern::List {
+                bracket: list.bracket.clone(),
+                names,
+            });
+        }
+        None
+    }
+
+    /// Targets that can be read and written back by compound assignment and `++`/`--`.
+    fn check_assignment_target(target: &Expr, operator: &Token) -> Result<(), ParserError> {
+        match target {
+            Expr::Variable(_) | Expr::Get(_) | Expr::Index(_) => Ok(()),
+            _ => Err(ParserError::InvalidAssignmentTarget {
+                lexeme: operator.lexeme.clone(),
+                line: operator.line,
+            }),
+        }
+    }
+
+    fn conditional(&mut self) -> ParseExprResult {
+        let condition = self.pipeline()?;
+
+        if self.token_match(&[TokenType::Question]) {
+            let then_branch = self.expression()?;
+            self.consume(
+                &TokenType::Colon,
+                "Expected ':' after then branch of conditional expression.",
+            )?;
+            let else_branch = self.conditional()?;
+            return Ok(Expr::Conditional(Conditional::new(
+                Box::new(condition),
+                Box::new(then_branch),
+                Box::new(else_branch),
+            )));
+        }
+        Ok(condition)
+    }
+
+    /// `x |> f(a)` is parsed as the call `f(x, a)`, and `x |> f` as `f(x)`.
+    fn pipeline(&mut self) -> ParseExprResult {
+        let mut expr = self.coalesce()?;
+
+        while self.token_match(&[TokenType::PipeGreater]) {
+            let operator = self.previous().clone();
+            let mut stage = self.coalesce()?;
+            let call = match &mut stage {
+                Expr::Call(call) => Some(call),
+                Expr::OptionalChain(chain) => match &mut *chain.expression {
+                    Expr::Call(call) => Some(call),
+                    _ => None,
+                },
+                _ => None,
             };
+            expr = match call {
+                Some(call) => {
+                    // the pipe replaces the closing paren so the printer can tell the forms apart
+                    call.arguments.insert(0, expr);
+                    call.paren = operator;
+                    stage
+                }
+                None => Expr::Call(Call::new(Box::new(stage), operator, vec![expr], false)),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    /// `a ?? b` binds looser than `or` and only evaluates `b` when `a` is nil.
+    fn coalesce(&mut self) -> ParseExprResult {
+        let mut expr = self.or()?;
 
-            println!("{}", err);
+        while self.token_match(&[TokenType::QuestionQuestion]) {
+            let operator = self.previous().clone();
+            let right = self.or()?;
+            expr = Expr::new_logical(Logical::new(Box::new(expr), operator, Box::new(right)))
         }
+
         Ok(expr)
     }
 
@@ -344,8 +834,14 @@ impl<'a> Parser<'a> {
         mem::discriminant(&self.peek()._type) == mem::discriminant(_type)
     }
 
+    fn check_next(&self, _type: &TokenType) -> bool {
+        self.tokens
+            .get(self.current + 1)
+            .is_some_and(|token| mem::discriminant(&token._type) == mem::discriminant(_type))
+    }
+
     fn comparison(&mut self) -> ParseExprResult {
-        let mut expr = self.term()?;
+        let mut expr = self.range()?;
 
         while self.token_match(&[
             TokenType::Greater,
@@ -354,13 +850,28 @@ impl<'a> Parser<'a> {
             TokenType::LessEqual,
         ]) {
             let operator = self.previous().clone();
-            let right = self.term()?;
+            let right = self.range()?;
             expr = Expr::Binary(Binary::new(Box::new(expr), operator, Box::new(right)));
         }
 
         Ok(expr)
     }
 
+    fn range(&mut self) -> ParseExprResult {
+        let start = self.term()?;
+
+        if self.token_match(&[TokenType::DotDot, TokenType::DotDotEqual]) {
+            let operator = self.previous().clone();
+            let end = self.term()?;
+            return Ok(Expr::Range(Range::new(
+                Box::new(start),
+                operator,
+                Box::new(end),
+            )));
+        }
+        Ok(start)
+    }
+
     fn term(&mut self) -> ParseExprResult {
         let mut expr = self.factor()?;
 
@@ -376,9 +887,14 @@ impl<'a> Parser<'a> {
     fn factor(&mut self) -> ParseExprResult {
         let mut expr = self.unary()?;
 
-        while self.token_match(&[TokenType::Slash, TokenType::Star]) {
+        while self.token_match(&[
+            TokenType::Slash,
+            TokenType::Star,
+            TokenType::Percent,
+            TokenType::TildeSlash,
+        ]) {
             let operator = self.previous().clone();
-            let right = self.factor()?;
+            let right = self.unary()?;
             expr = Expr::Binary(Binary::new(Box::new(expr), operator, Box::new(right)));
         }
         Ok(expr)
@@ -390,22 +906,95 @@ impl<'a> Parser<'a> {
             let right = self.unary()?;
             return Ok(Expr::Unary(Unary::new(operator, Box::new(right))));
         }
-        self.call()
+        if self.token_match(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous().clone();
+            let target = self.unary()?;
+            Parser::check_assignment_target(&target, &operator)?;
+            return Ok(Expr::Increment(Increment::new(
+                Box::new(target),
+                operator,
+                true,
+            )));
+        }
+        self.power()
+    }
+
+    /// `**` binds tighter than a unary operator on its left (`-2 ** 2` is `-(2 ** 2)`),
+    /// but its right operand may itself be unary (`2 ** -1`), and it is right-associative.
+    fn power(&mut self) -> ParseExprResult {
+        let expr = self.postfix()?;
+
+        if self.token_match(&[TokenType::StarStar]) {
+            let operator = self.previous().clone();
+            let right = self.unary()?;
+            return Ok(Expr::Binary(Binary::new(
+                Box::new(expr),
+                operator,
+                Box::new(right),
+            )));
+        }
+        Ok(expr)
+    }
+
+    fn postfix(&mut self) -> ParseExprResult {
+        let expr = self.call()?;
+
+        if self.token_match(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous().clone();
+            Parser::check_assignment_target(&expr, &operator)?;
+            return Ok(Expr::Increment(Increment::new(
+                Box::new(expr),
+                operator,
+                false,
+            )));
+        }
+        Ok(expr)
     }
 
     fn call(&mut self) -> ParseExprResult {
         let mut expr = self.primary()?;
+        // A `?.` anywhere in the chain makes the rest of it short-circuit to nil.
+        let mut optional_chain = false;
 
         loop {
-            if !self.token_match(&[TokenType::LeftParen]) {
+            if self.token_match(&[TokenType::LeftParen]) {
+                expr = self.finish_call(expr, false)?;
+            } else if self.token_match(&[TokenType::Dot]) {
+                let name = self.property_name()?;
+                expr = Expr::Get(Get::new(Box::new(expr), name, false));
+            } else if self.token_match(&[TokenType::QuestionDot]) {
+                optional_chain = true;
+                if self.token_match(&[TokenType::LeftParen]) {
+                    expr = self.finish_call(expr, true)?;
+                } else {
+                    let name = self.property_name()?;
+                    expr = Expr::Get(Get::new(Box::new(expr), name, true));
+                }
+            } else if self.token_match(&[TokenType::LeftBracket]) {
+                let bracket = self.previous().clone();
+                let index = self.expression()?;
+                self.consume(&TokenType::RightBracket, "Expected ']' after index.")?;
+                expr = Expr::Index(Index::new(Box::new(expr), bracket, Box::new(index)));
+            } else {
                 break;
             }
-            expr = self.finish_call(expr)?;
+        }
+        if optional_chain {
+            return Ok(Expr::OptionalChain(OptionalChain::new(Box::new(expr))));
         }
         Ok(expr)
     }
 
-    fn finish_call(&mut self, callee: Expr) -> ParseExprResult {
+    fn property_name(&mut self) -> Result<Token, ParserError> {
+        Ok(self
+            .consume(
+                &TokenType::Identifier(String::new()),
+                "Expected property name after '.'",
+            )?
+            .clone())
+    }
+
+    fn finish_call(&mut self, callee: Expr, optional: bool) -> ParseExprResult {
         let mut arguments = vec![];
         if !self.check(&TokenType::RightParen) {
             loop {
@@ -428,6 +1017,7 @@ impl<'a> Parser<'a> {
             Box::new(callee),
             paren.clone(),
             arguments,
+            optional,
         )))
     }
 
@@ -444,6 +1034,7 @@ impl<'a> Parser<'a> {
 
         if self.token_match(&[
             TokenType::Number(0f64),
+            TokenType::Integer(0),
             TokenType::String(String::new()),
             TokenType::Identifier(String::new()),
         ]) {
@@ -451,6 +1042,9 @@ impl<'a> Parser<'a> {
                 TokenType::Number(num) => {
                     return Ok(Expr::Literal(Literal::new(Value::Number(*num))))
                 }
+                TokenType::Integer(num) => {
+                    return Ok(Expr::Literal(Literal::new(Value::Integer(*num))))
+                }
                 TokenType::String(string) => {
                     return Ok(Expr::Literal(Literal::new(Value::String(string.clone()))))
                 }
@@ -461,21 +1055,336 @@ impl<'a> Parser<'a> {
             }
         }
 
+        if self.token_match(&[TokenType::This]) {
+            return Ok(Expr::This(This::new(self.previous().clone())));
+        }
+        if self.token_match(&[TokenType::Super]) {
+            let keyword = self.previous().clone();
+            self.consume(&TokenType::Dot, "Expected '.' after 'super'")?;
+            let method = self
+                .consume(
+                    &TokenType::Identifier(String::new()),
+                    "Expected superclass method name",
+                )?
+                .clone();
+            return Ok(Expr::Super_(Super_::new(keyword, method)));
+        }
+
         if self.token_match(&[TokenType::LeftParen]) {
             let expr = self.expression()?;
             self.consume(&TokenType::RightParen, "Expected ')' after expression.")?;
             return Ok(Expr::Grouping(Grouping::new(Box::new(expr))));
         }
+        if self.token_match(&[TokenType::LeftBracket]) {
+            return self.list();
+        }
+        if self.token_match(&[TokenType::LeftBrace]) {
+            return self.map();
+        }
+        if self.token_match(&[TokenType::Match]) {
+            return self.match_expression();
+        }
         Err(Parser::error(self.peek(), "Expected expression."))
     }
 
+    /// `[a, b, c]`, after the opening bracket. A trailing comma is allowed.
+    fn list(&mut self) -> ParseExprResult {
+        let bracket = self.previous().clone();
+        let mut elements = vec![];
+        while !self.check(&TokenType::RightBracket) {
+            elements.push(self.expression()?);
+            if !self.token_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            &TokenType::RightBracket,
+            "Expected ']' after list elements.",
+        )?;
+        Ok(Expr::List(List::new(bracket, elements)))
+    }
+
+    /// `{key: value, ...}`, after the opening brace. A trailing comma is allowed.
+    fn map(&mut self) -> ParseExprResult {
+        let brace = self.previous().clone();
+        let mut keys = vec![];
+        let mut values = vec![];
+        while !self.check(&TokenType::RightBrace) {
+            keys.push(self.expression()?);
+            self.consume(&TokenType::Colon, "Expected ':' after map key.")?;
+            values.push(self.expression()?);
+            if !self.token_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&TokenType::RightBrace, "Expected '}' after map entries.")?;
+        Ok(Expr::Map(Map::new(brace, keys, values)))
+    }
+
+    /// `class Name { method() { ... } ... }`, after the `class` keyword.
+    fn class_declaration(&mut self) -> ParseStmtResult {
+        let name = self
+            .consume(&TokenType::Identifier(String::new()), "Expected class name")?
+            .clone();
+        let mut superclass = None;
+        if self.token_match(&[TokenType::Less]) {
+            let name = self.consume(
+                &TokenType::Identifier(String::new()),
+                "Expected superclass name",
+            )?;
+            superclass = Some(Expr::Variable(Variable::new(name.clone())));
+        }
+        let mut traits = vec![];
+        if self.token_match(&[TokenType::With]) {
+            loop {
+                let name =
+                    self.consume(&TokenType::Identifier(String::new()), "Expected trait name")?;
+                traits.push(Expr::Variable(Variable::new(name.clone())));
+                if !self.token_match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(&TokenType::LeftBrace, "Expected '{' before class body")?;
+        let mut methods = vec![];
+        let mut class_methods = vec![];
+        let mut getters = vec![];
+        let mut setters = vec![];
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            // `class name(...)` declares a class method, `name { ... }` a getter and
+            // `set name(value) { ... }` a setter
+            let (kind, members) = if self.token_match(&[TokenType::Class]) {
+                (FunctionKind::Method, &mut class_methods)
+            } else if self.check_next(&TokenType::LeftBrace) {
+                (FunctionKind::Getter, &mut getters)
+            } else if self.peek().lexeme == "set"
+                && self.check_next(&TokenType::Identifier(String::new()))
+            {
+                self.advance();
+                (FunctionKind::Setter, &mut setters)
+            } else {
+                (FunctionKind::Method, &mut methods)
+            };
+            if let Stmt::Function(method) = self.function(kind)? {
+                members.push(method);
+            }
+        }
+        self.consume(&TokenType::RightBrace, "Expected '}' after class body")?;
+        Ok(Stmt::new_class(Class::new(
+            name,
+            superclass,
+            traits,
+            methods,
+            class_methods,
+            getters,
+            setters,
+        )))
+    }
+
+    /// `trait Comparable { ... }`, after the `trait` keyword. Its methods are mixed into
+    /// classes declared `with` it.
+    fn trait_declaration(&mut self) -> ParseStmtResult {
+        let name = self
+            .consume(&TokenType::Identifier(String::new()), "Expected trait name")?
+            .clone();
+        self.consume(&TokenType::LeftBrace, "Expected '{' before trait body")?;
+        let mut methods = vec![];
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            if let Stmt::Function(method) = self.function(FunctionKind::Method)? {
+                methods.push(method);
+            }
+        }
+        self.consume(&TokenType::RightBrace, "Expected '}' after trait body")?;
+        Ok(Stmt::new_trait_(Trait_::new(name, methods)))
+    }
+
+    /// `enum Shape { Circle(r), Rect(w, h), Empty }`, after the `enum` keyword.
+    fn enum_declaration(&mut self) -> ParseStmtResult {
+        let name = self
+            .consume(&TokenType::Identifier(String::new()), "Expected enum name")?
+            .clone();
+        self.consume(&TokenType::LeftBrace, "Expected '{' after enum name")?;
+        let mut variants = vec![];
+        while !self.check(&TokenType::RightBrace) {
+            let variant = self
+                .consume(
+                    &TokenType::Identifier(String::new()),
+                    "Expected variant name",
+                )?
+                .clone();
+            let mut fields = vec![];
+            if self.token_match(&[TokenType::LeftParen]) {
+                while !self.check(&TokenType::RightParen) {
+                    let field = self
+                        .consume(&TokenType::Identifier(String::new()), "Expected field name")?
+                        .clone();
+                    fields.push(field);
+                    if !self.token_match(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+                self.consume(&TokenType::RightParen, "Expected ')' after variant fields")?;
+            }
+            variants.push(EnumVariant {
+                name: variant,
+                fields,
+            });
+            if !self.token_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&TokenType::RightBrace, "Expected '}' after enum variants")?;
+        Ok(Stmt::new_enum_(Enum_::new(name, variants)))
+    }
+
+    /// `match (subject) {`, which starts both `match` statements and expressions.
+    fn match_subject(&mut self) -> Result<(Token, Expr), ParserError> {
+        let keyword = self.previous().clone();
+        self.consume(&TokenType::LeftParen, "Expected '(' after 'match'")?;
+        let subject = self.expression()?;
+        self.consume(&TokenType::RightParen, "Expected ')' after match subject")?;
+        self.consume(&TokenType::LeftBrace, "Expected '{' before match arms")?;
+        Ok((keyword, subject))
+    }
+
+    /// A `match` at the start of a statement, whose arms run statements:
+    /// `match (shape) { Circle(r) => print r; _ => { ... } }`.
+    fn match_statement(&mut self) -> ParseStmtResult {
+        let (keyword, subject) = self.match_subject()?;
+        let mut arms = vec![];
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            let pattern = self.match_pattern()?;
+            self.consume(&TokenType::FatArrow, "Expected '=>' after pattern")?;
+            let body = self.statement()?;
+            self.token_match(&[TokenType::Comma]);
+            arms.push(Arm { pattern, body });
+        }
+        self.consume(&TokenType::RightBrace, "Expected '}' after match arms")?;
+        Ok(Stmt::new_match_stmt(MatchStmt::new(keyword, subject, arms)))
+    }
+
+    /// A `match` inside an expression, whose comma-separated arms evaluate to its value:
+    /// `var area = match (shape) { Circle(r) => 3 * r * r, _ => 0 };`.
+    fn match_expression(&mut self) -> ParseExprResult {
+        let (keyword, subject) = self.match_subject()?;
+        let mut arms = vec![];
+        while !self.check(&TokenType::RightBrace) {
+            let pattern = self.match_pattern()?;
+            self.consume(&TokenType::FatArrow, "Expected '=>' after pattern")?;
+            let body = self.expression()?;
+            arms.push(Arm { pattern, body });
+            if !self.token_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&TokenType::RightBrace, "Expected '}' after match arms")?;
+        Ok(Expr::Match_(Match_::new(keyword, Box::new(subject), arms)))
+    }
+
+    /// `switch (x) { case 1, 2: ... default: ... }` runs the statements of the first
+    /// case with a value equal to `x`, or of `default`, without falling through.
+    fn switch_statement(&mut self) -> ParseStmtResult {
+        let keyword = self.previous().clone();
+        self.consume(&TokenType::LeftParen, "Expected '(' after 'switch'")?;
+        let subject = self.expression()?;
+        self.consume(&TokenType::RightParen, "Expected ')' after switch subject")?;
+        self.consume(&TokenType::LeftBrace, "Expected '{' before switch cases")?;
+        let mut cases = vec![];
+        let mut default_branch = None;
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            if self.token_match(&[TokenType::Case]) {
+                let keyword = self.previous().clone();
+                let mut values = vec![self.expression()?];
+                while self.token_match(&[TokenType::Comma]) {
+                    values.push(self.expression()?);
+                }
+                self.consume(&TokenType::Colon, "Expected ':' after case values")?;
+                let body = self.case_body()?;
+                cases.push(SwitchCase {
+                    keyword,
+                    values,
+                    body,
+                });
+            } else if self.token_match(&[TokenType::Default]) {
+                let line = self.previous().line;
+                self.consume(&TokenType::Colon, "Expected ':' after 'default'")?;
+                if default_branch.is_some() {
+                    return Err(ParserError::DuplicateDefault { line });
+                }
+                default_branch = Some(Box::new(self.case_body()?));
+            } else {
+                return Err(Parser::error(self.peek(), "Expected 'case' or 'default'"));
+            }
+        }
+        self.consume(&TokenType::RightBrace, "Expected '}' after switch cases")?;
+        Ok(Stmt::new_switch(Switch::new(
+            keyword,
+            subject,
+            cases,
+            default_branch,
+        )))
+    }
+
+    /// The statements up to the next `case`, `default` or the end of the `switch`.
+    fn case_body(&mut self) -> ParseStmtResult {
+        let mut statements = vec![];
+        while !self.check(&TokenType::Case)
+            && !self.check(&TokenType::Default)
+            && !self.check(&TokenType::RightBrace)
+            && !self.is_at_end()
+        {
+            statements.push(self.declaration()?);
+        }
+        Ok(Stmt::new_block(Block::new(statements)))
+    }
+
+    fn match_pattern(&mut self) -> Result<MatchPattern, ParserError> {
+        let token = self.advance().clone();
+        let value = match &token._type {
+            TokenType::Identifier(_) if token.lexeme == "_" => {
+                return Ok(MatchPattern::Wildcard(token))
+            }
+            TokenType::Identifier(_) if self.token_match(&[TokenType::LeftParen]) => {
+                let mut fields = vec![];
+                while !self.check(&TokenType::RightParen) {
+                    fields.push(self.match_pattern()?);
+                    if !self.token_match(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+                self.consume(&TokenType::RightParen, "Expected ')' after variant fields")?;
+                return Ok(MatchPattern::Variant {
+                    name: token,
+                    fields,
+                });
+            }
+            TokenType::Identifier(_) => return Ok(MatchPattern::Identifier(token)),
+            TokenType::Integer(num) => Value::Integer(*num),
+            TokenType::Number(num) => Value::Number(*num),
+            TokenType::String(string) => Value::String(string.clone()),
+            TokenType::True => Value::Bool(true),
+            TokenType::False => Value::Bool(false),
+            TokenType::Nil => Value::Nil(Nil),
+            TokenType::Minus => match self.advance()._type {
+                TokenType::Integer(num) => Value::Integer(-num),
+                TokenType::Number(num) => Value::Number(-num),
+                _ => {
+                    return Err(Parser::error(
+                        self.previous(),
+                        "Expected a number after '-'",
+                    ))
+                }
+            },
+            _ => return Err(Parser::error(&token, "Expected a pattern")),
+        };
+        Ok(MatchPattern::Literal(token, value))
+    }
+
     fn consume(&mut self, _type: &TokenType, msg: &str) -> Result<&Token, ParserError> {
         if self.check(_type) {
             return Ok(self.advance());
         }
-        let error = Parser::error(self.peek(), msg);
-        println!("{}", error);
-        Err(error)
+        Err(Parser::error(self.peek(), msg))
     }
 
     fn error(token: &Token, msg: &str) -> ParserError {
@@ -486,6 +1395,7 @@ impl<'a> Parser<'a> {
             },
             _ => ParserError::UnexpectedToken {
                 line: token.line,
+                column: token.column,
                 lexeme: token.lexeme.clone(),
                 msg: msg.to_string(),
             },
@@ -504,17 +1414,28 @@ impl<'a> Parser<'a> {
                 TokenType::Class
                 | TokenType::Fun
                 | TokenType::Var
+                | TokenType::Const
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Return
-                | TokenType::Print => {
+                | TokenType::Print
+                | TokenType::Throw
+                | TokenType::Yield
+                | TokenType::Try
+                | TokenType::Import
+                | TokenType::From
+                | TokenType::Enum
+                | TokenType::Match
+                | TokenType::Switch
+                | TokenType::Trait
+                | TokenType::Defer => {
                     return;
                 }
                 _ => {}
             }
-        }
 
-        self.advance();
+            self.advance();
+        }
     }
 }
@@ -1,28 +1,102 @@
-use crate::{error::ResolverError, token::Token};
-use std::{collections::HashMap, error::Error};
+use crate::{
+    error::{ResolverError, ResolverWarning},
+    token::{Token, TokenType},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
 
-use crate::{ast::*, interpreter::Interpreter};
+use crate::ast::*;
 
 type ResolverResult = Result<(), ResolverError>;
+
+#[derive(Clone, Copy)]
+struct Local {
+    defined: bool,
+    constant: bool,
+}
+
+/// The kind of declaration whose members are being resolved, which decides where
+/// `super` may be used.
+#[derive(Clone, Copy, PartialEq)]
+enum ClassKind {
+    Class,
+    Subclass,
+    Trait,
+}
+
 pub struct Resolver {
-    interpreter: Interpreter,
-    scopes: Vec<HashMap<String, bool>>,
+    scopes: Vec<HashMap<String, Local>>,
+    function_depth: usize,
+    /// Whether the innermost function being resolved is a generator.
+    in_generator: bool,
+    /// The classes and traits enclosing the code being resolved, innermost last.
+    classes: Vec<ClassKind>,
+    /// Whether the innermost class member being resolved is a class method, which has no `this`.
+    in_class_method: bool,
+    /// The variants of each declared enum, by enum name.
+    enums: HashMap<String, Vec<String>>,
+    /// The enum and field count of each declared variant, by variant name.
+    variants: HashMap<String, (String, usize)>,
+    warnings: Vec<ResolverWarning>,
 }
 
 impl Resolver {
-    fn new(interpreter: Interpreter) -> Self {
+    pub fn new() -> Self {
         Self {
-            interpreter,
             scopes: Vec::new(),
+            function_depth: 0,
+            in_generator: false,
+            classes: Vec::new(),
+            in_class_method: false,
+            enums: HashMap::new(),
+            variants: HashMap::new(),
+            warnings: Vec::new(),
         }
     }
 
-    fn resolve_local(&mut self, expr: Expr, name: &Token) -> ResolverResult {
-        for (i, scope) in self.scopes.iter().rev().enumerate() {
-            if scope.contains_key(&name.lexeme) {
-                self.interpreter
-                    .resolve_expr(&expr, self.scopes.len() - 1 - i);
-            }
+    pub fn resolve(&mut self, statements: &[Stmt]) -> ResolverResult {
+        self.resolve_stmts(statements)
+    }
+
+    pub fn warnings(&self) -> &[ResolverWarning] {
+        &self.warnings
+    }
+
+    pub fn into_warnings(self) -> Vec<ResolverWarning> {
+        self.warnings
+    }
+
+    fn resolve_local(&self, name: &Token) -> Option<&Local> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.lexeme))
+    }
+
+    /// Constant locals are rejected here; globals are not tracked in `scopes`,
+    /// so assignments to constant globals are rejected at runtime instead.
+    fn check_assignable(&self, name: &Token) -> ResolverResult {
+        match self.resolve_local(name) {
+            Some(local) if local.constant => Err(ResolverError::AssignToConstant {
+                lexeme: name.lexeme.clone(),
+                line: name.line,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolves methods that are bound to an instance, so can use `this`.
+    fn resolve_methods<'a>(
+        &mut self,
+        methods: impl Iterator<Item = &'a Function>,
+    ) -> ResolverResult {
+        for method in methods {
+            self.begin_scope();
+            self.define(&Token::new(TokenType::This, "this", method.name.line))?;
+            self.resolve_function(method)?;
+            self.end_scope();
         }
         Ok(())
     }
@@ -42,17 +116,126 @@ impl Resolver {
         self.visit_expr(expr)
     }
 
+    fn resolve_assignment_target(&mut self, target: &Expr) -> ResolverResult {
+        self.resolve_expr(target)?;
+        if let Expr::Variable(variable) = target {
+            self.check_assignable(&variable.name)?;
+        }
+        Ok(())
+    }
+
     fn resolve_function(&mut self, function: &Function) -> ResolverResult {
+        let enclosing_generator = mem::replace(&mut self.in_generator, function.generator);
+        self.function_depth += 1;
         self.begin_scope();
-        function.params.iter().for_each(|param| {
-            self.declare(param);
-            self.define(param);
-        });
+        let required = function.params.len() - function.defaults.len();
+        for (i, param) in function.params.iter().enumerate() {
+            self.declare(param)?;
+            if let Some(default) = i.checked_sub(required) {
+                self.resolve_expr(&function.defaults[default])?;
+            }
+            self.define(param)?;
+        }
+        if let Some(rest) = &function.rest {
+            self.declare(rest)?;
+            self.define(rest)?;
+        }
         self.resolve_stmts(&function.body)?;
         self.end_scope();
+        self.function_depth -= 1;
+        self.in_generator = enclosing_generator;
         Ok(())
     }
 
+    fn resolve_arms<T>(
+        &mut self,
+        keyword: &Token,
+        subject: &Expr,
+        arms: &[Arm<T>],
+        resolve_body: impl Fn(&mut Self, &T) -> ResolverResult,
+    ) -> ResolverResult {
+        self.resolve_expr(subject)?;
+        for arm in arms.iter() {
+            self.begin_scope();
+            self.define_bindings(&arm.pattern)?;
+            resolve_body(self, &arm.body)?;
+            self.end_scope();
+        }
+        self.check_exhaustive(keyword, arms.iter().map(|arm| &arm.pattern));
+        Ok(())
+    }
+
+    fn define_bindings(&mut self, pattern: &MatchPattern) -> ResolverResult {
+        match pattern {
+            MatchPattern::Identifier(name) if !self.is_unit_variant(name) => self.define(name),
+            MatchPattern::Variant { fields, .. } => {
+                for field in fields.iter() {
+                    self.define_bindings(field)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn is_unit_variant(&self, name: &Token) -> bool {
+        self.variants
+            .get(&name.lexeme)
+            .is_some_and(|(_, fields)| *fields == 0)
+    }
+
+    /// Whether `pattern` matches every value.
+    fn is_irrefutable(&self, pattern: &MatchPattern) -> bool {
+        match pattern {
+            MatchPattern::Wildcard(_) => true,
+            MatchPattern::Identifier(name) => !self.is_unit_variant(name),
+            _ => false,
+        }
+    }
+
+    /// Warns about a `match` on the variants of an enum that leaves some of them out
+    /// and has no arm matching everything else.
+    fn check_exhaustive<'a>(
+        &mut self,
+        keyword: &Token,
+        patterns: impl Iterator<Item = &'a MatchPattern>,
+    ) {
+        let mut enum_name = None;
+        let mut covered = HashSet::new();
+        for pattern in patterns {
+            if self.is_irrefutable(pattern) {
+                return;
+            }
+            let (variant, fields) = match pattern {
+                MatchPattern::Variant { name, fields } => (name, fields.as_slice()),
+                MatchPattern::Identifier(name) => (name, &[][..]),
+                _ => continue,
+            };
+            if let Some((owner, _)) = self.variants.get(&variant.lexeme) {
+                enum_name.get_or_insert_with(|| owner.clone());
+                if fields.iter().all(|field| self.is_irrefutable(field)) {
+                    covered.insert(variant.lexeme.clone());
+                }
+            }
+        }
+        let enum_name = match enum_name {
+            Some(enum_name) => enum_name,
+            None => return,
+        };
+        let missing: Vec<&str> = self.enums[&enum_name]
+            .iter()
+            .filter(|variant| !covered.contains(*variant))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            self.warnings.push(ResolverWarning::NonExhaustiveMatch {
+                missing: missing.join(", "),
+                enum_name,
+                line: keyword.line,
+            });
+        }
+    }
+
     fn begin_scope(&mut self) {
         self.scopes.push(HashMap::new())
     }
@@ -61,28 +244,52 @@ impl Resolver {
         self.scopes.pop();
     }
 
-    fn declare(&mut self, name: &Token) {
-        if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name.lexeme.clone(), false);
-        }
+    fn declare(&mut self, name: &Token) -> ResolverResult {
+        self.insert(name, false, false)
+    }
+
+    fn define(&mut self, name: &Token) -> ResolverResult {
+        self.insert(name, true, false)
     }
-    fn define(&mut self, name: &Token) {
+
+    fn define_constant(&mut self, name: &Token) -> ResolverResult {
+        self.insert(name, true, true)
+    }
+
+    /// Adds `name` to the innermost scope, which must not already hold it as a constant.
+    fn insert(&mut self, name: &Token, defined: bool, constant: bool) -> ResolverResult {
         if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name.lexeme.clone(), true);
+            if scope.get(&name.lexeme).is_some_and(|local| local.constant) {
+                return Err(ResolverError::RedeclareConstant {
+                    lexeme: name.lexeme.clone(),
+                    line: name.line,
+                });
+            }
+            scope.insert(name.lexeme.clone(), Local { defined, constant });
         }
+        Ok(())
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl StmtVisitor<ResolverResult> for Resolver {
     fn visit_expression(&mut self, expression: &Expression) -> ResolverResult {
-        self.resolve_stmt(&Stmt::Expression(expression.clone()))
+        self.resolve_expr(&expression.expression)
     }
 
     fn visit_function(&mut self, function: &Function) -> ResolverResult {
-        self.declare(&function.name);
-        self.define(&function.name);
+        for decorator in function.decorators.iter() {
+            self.resolve_expr(decorator)?;
+        }
+        self.declare(&function.name)?;
+        self.define(&function.name)?;
 
-        self.resolve_function(&function)
+        self.resolve_function(function)
     }
 
     fn visit_if_(&mut self, if_: &If_) -> ResolverResult {
@@ -103,6 +310,16 @@ impl StmtVisitor<ResolverResult> for Resolver {
         self.resolve_stmt(&while_.body)
     }
 
+    fn visit_for_in(&mut self, for_in: &ForIn) -> ResolverResult {
+        self.resolve_expr(&for_in.iterable)?;
+        self.begin_scope();
+        self.declare(&for_in.name)?;
+        self.define(&for_in.name)?;
+        self.resolve_stmt(&for_in.body)?;
+        self.end_scope();
+        Ok(())
+    }
+
     fn visit_return_(&mut self, return_: &Return_) -> ResolverResult {
         if let Some(return_value) = &return_.value {
             self.resolve_expr(return_value)?;
@@ -110,12 +327,173 @@ impl StmtVisitor<ResolverResult> for Resolver {
         Ok(())
     }
 
+    fn visit_yield_(&mut self, yield_: &Yield_) -> ResolverResult {
+        if self.function_depth == 0 {
+            return Err(ResolverError::YieldOutsideFunction {
+                line: yield_.keyword.line,
+            });
+        }
+        if let Some(value) = &yield_.value {
+            self.resolve_expr(value)?;
+        }
+        Ok(())
+    }
+
+    /// Generators run their own statements, so they have no blocks to defer to.
+    fn visit_defer(&mut self, defer: &Defer) -> ResolverResult {
+        let line = defer.keyword.line;
+        if self.scopes.is_empty() {
+            return Err(ResolverError::DeferOutsideBlock { line });
+        }
+        if self.in_generator {
+            return Err(ResolverError::DeferInGenerator { line });
+        }
+        self.resolve_expr(&defer.expression)
+    }
+
+    fn visit_throw(&mut self, throw: &Throw) -> ResolverResult {
+        self.resolve_expr(&throw.value)
+    }
+
+    fn visit_try_(&mut self, try_: &Try_) -> ResolverResult {
+        self.begin_scope();
+        self.resolve_stmts(&try_.body)?;
+        self.end_scope();
+
+        if let Some(catch_name) = &try_.catch_name {
+            self.begin_scope();
+            self.declare(catch_name)?;
+            self.define(catch_name)?;
+            self.resolve_stmts(&try_.catch_body)?;
+            self.end_scope();
+        }
+
+        self.begin_scope();
+        self.resolve_stmts(&try_.finally_body)?;
+        self.end_scope();
+        Ok(())
+    }
+
+    fn visit_import(&mut self, import: &Import) -> ResolverResult {
+        for name in import.alias.iter().chain(import.names.iter()) {
+            self.define_constant(name)?;
+        }
+        Ok(())
+    }
+
     fn visit_var(&mut self, var: &Var) -> ResolverResult {
-        self.declare(&var.name);
+        self.declare(&var.name)?;
         if let Some(init) = &var.initializer {
             self.resolve_expr(init)?;
         }
-        self.define(&var.name);
+        if var.constant {
+            self.define_constant(&var.name)?;
+        } else {
+            self.define(&var.name)?;
+        }
+        Ok(())
+    }
+
+    fn visit_destructure(&mut self, destructure: &Destructure) -> ResolverResult {
+        for name in destructure.pattern.names() {
+            self.declare(name)?;
+        }
+        self.resolve_expr(&destructure.initializer)?;
+        for name in destructure.pattern.names() {
+            if destructure.constant {
+                self.define_constant(name)?;
+            } else {
+                self.define(name)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_class(&mut self, class: &Class) -> ResolverResult {
+        self.declare(&class.name)?;
+        self.define(&class.name)?;
+
+        if let Some(Expr::Variable(superclass)) = &class.superclass {
+            if superclass.name.lexeme == class.name.lexeme {
+                return Err(ResolverError::InheritFromSelf {
+                    line: superclass.name.line,
+                });
+            }
+        }
+        for expr in class.superclass.iter().chain(class.traits.iter()) {
+            self.resolve_expr(expr)?;
+        }
+
+        let kind = match class.superclass {
+            Some(_) => ClassKind::Subclass,
+            None => ClassKind::Class,
+        };
+        let instance_members = class
+            .methods
+            .iter()
+            .chain(class.getters.iter())
+            .chain(class.setters.iter());
+        let enclosing_class_method = self.in_class_method;
+        self.classes.push(kind);
+        self.in_class_method = false;
+        self.resolve_methods(instance_members)?;
+        self.in_class_method = true;
+        for method in class.class_methods.iter() {
+            self.resolve_function(method)?;
+        }
+        self.classes.pop();
+        self.in_class_method = enclosing_class_method;
+        Ok(())
+    }
+
+    fn visit_trait_(&mut self, trait_: &Trait_) -> ResolverResult {
+        self.declare(&trait_.name)?;
+        self.define(&trait_.name)?;
+
+        let enclosing_class_method = self.in_class_method;
+        self.classes.push(ClassKind::Trait);
+        self.in_class_method = false;
+        self.resolve_methods(trait_.methods.iter())?;
+        self.classes.pop();
+        self.in_class_method = enclosing_class_method;
+        Ok(())
+    }
+
+    fn visit_enum_(&mut self, enum_: &Enum_) -> ResolverResult {
+        self.define(&enum_.name)?;
+        let mut variants = vec![];
+        for variant in enum_.variants.iter() {
+            self.define(&variant.name)?;
+            self.variants.insert(
+                variant.name.lexeme.clone(),
+                (enum_.name.lexeme.clone(), variant.fields.len()),
+            );
+            variants.push(variant.name.lexeme.clone());
+        }
+        self.enums.insert(enum_.name.lexeme.clone(), variants);
+        Ok(())
+    }
+
+    fn visit_match_stmt(&mut self, match_stmt: &MatchStmt) -> ResolverResult {
+        self.resolve_arms(
+            &match_stmt.keyword,
+            &match_stmt.subject,
+            &match_stmt.arms,
+            Self::resolve_stmt,
+        )
+    }
+
+    fn visit_switch(&mut self, switch: &Switch) -> ResolverResult {
+        self.resolve_expr(&switch.subject)?;
+        for case in switch.cases.iter() {
+            for value in case.values.iter() {
+                self.resolve_expr(value)?;
+            }
+            self.resolve_stmt(&case.body)?;
+        }
+        if let Some(default_branch) = &switch.default_branch {
+            self.resolve_stmt(default_branch)?;
+        }
         Ok(())
     }
 
@@ -130,7 +508,18 @@ impl StmtVisitor<ResolverResult> for Resolver {
 impl ExprVisitor<ResolverResult> for Resolver {
     fn visit_assign(&mut self, assign: &Assign) -> ResolverResult {
         self.resolve_expr(&assign.value)?;
-        self.resolve_local(Expr::Assign(assign.clone()), &assign.name)
+        self.check_assignable(&assign.name)
+    }
+
+    fn visit_destructure_assign(
+        &mut self,
+        destructure_assign: &DestructureAssign,
+    ) -> ResolverResult {
+        self.resolve_expr(&destructure_assign.value)?;
+        for name in destructure_assign.pattern.names() {
+            self.check_assignable(name)?;
+        }
+        Ok(())
     }
 
     fn visit_binary(&mut self, binary: &Binary) -> ResolverResult {
@@ -146,14 +535,109 @@ impl ExprVisitor<ResolverResult> for Resolver {
         Ok(())
     }
 
+    fn visit_compound_assign(&mut self, compound_assign: &CompoundAssign) -> ResolverResult {
+        self.resolve_expr(&compound_assign.value)?;
+        self.resolve_assignment_target(&compound_assign.target)
+    }
+
+    fn visit_increment(&mut self, increment: &Increment) -> ResolverResult {
+        self.resolve_assignment_target(&increment.target)
+    }
+
+    fn visit_conditional(&mut self, conditional: &Conditional) -> ResolverResult {
+        self.resolve_expr(&conditional.condition)?;
+        self.resolve_expr(&conditional.then_branch)?;
+        self.resolve_expr(&conditional.else_branch)
+    }
+
+    fn visit_get(&mut self, get: &Get) -> ResolverResult {
+        self.resolve_expr(&get.object)
+    }
+
+    fn visit_set(&mut self, set: &Set) -> ResolverResult {
+        self.resolve_expr(&set.value)?;
+        self.resolve_expr(&set.object)
+    }
+
+    fn visit_this(&mut self, this: &This) -> ResolverResult {
+        if self.in_class_method {
+            return Err(ResolverError::ThisInClassMethod {
+                line: this.keyword.line,
+            });
+        }
+        if self.classes.is_empty() {
+            return Err(ResolverError::ThisOutsideClass {
+                line: this.keyword.line,
+            });
+        }
+        Ok(())
+    }
+
+    fn visit_super_(&mut self, super_: &Super_) -> ResolverResult {
+        let line = super_.keyword.line;
+        if self.in_class_method {
+            return Err(ResolverError::SuperInClassMethod { line });
+        }
+        match self.classes.last() {
+            None => Err(ResolverError::SuperOutsideClass { line }),
+            Some(ClassKind::Trait) => Err(ResolverError::SuperInTrait { line }),
+            Some(ClassKind::Class) => Err(ResolverError::SuperWithoutSuperclass { line }),
+            Some(ClassKind::Subclass) => Ok(()),
+        }
+    }
+
+    fn visit_index(&mut self, index: &Index) -> ResolverResult {
+        self.resolve_expr(&index.object)?;
+        self.resolve_expr(&index.index)
+    }
+
+    fn visit_set_index(&mut self, set_index: &SetIndex) -> ResolverResult {
+        self.resolve_expr(&set_index.value)?;
+        self.resolve_expr(&set_index.object)?;
+        self.resolve_expr(&set_index.index)
+    }
+
+    fn visit_optional_chain(&mut self, optional_chain: &OptionalChain) -> ResolverResult {
+        self.resolve_expr(&optional_chain.expression)
+    }
+
     fn visit_grouping(&mut self, grouping: &Grouping) -> ResolverResult {
         self.resolve_expr(&grouping.expression)
     }
 
+    fn visit_list(&mut self, list: &List) -> ResolverResult {
+        for element in list.elements.iter() {
+            self.resolve_expr(element)?;
+        }
+        Ok(())
+    }
+
     fn visit_literal(&mut self, _literal: &Literal) -> ResolverResult {
         Ok(())
     }
 
+    fn visit_map(&mut self, map: &Map) -> ResolverResult {
+        for (key, value) in map.keys.iter().zip(map.values.iter()) {
+            self.resolve_expr(key)?;
+            self.resolve_expr(value)?;
+        }
+        Ok(())
+    }
+
+    fn visit_match_(&mut self, match_: &Match_) -> ResolverResult {
+        self.resolve_arms(
+            &match_.keyword,
+            &match_.subject,
+            &match_.arms,
+            Self::resolve_expr,
+        )
+    }
+
+    fn visit_range(&mut self, range: &Range) -> ResolverResult {
+        self.resolve_expr(&range.start)?;
+        self.resolve_expr(&range.end)
+    }
+
     fn visit_logical(&mut self, logical: &Logical) -> ResolverResult {
         self.resolve_expr(&logical.left)?;
         self.resolve_expr(&logical.right)
@@ -165,8 +649,8 @@ impl ExprVisitor<ResolverResult> for Resolver {
 
     fn visit_variable(&mut self, variable: &Variable) -> ResolverResult {
         if let Some(scope) = self.scopes.last() {
-            if let Some(initialized) = scope.get(&variable.name.lexeme) {
-                if !initialized {
+            if let Some(local) = scope.get(&variable.name.lexeme) {
+                if !local.defined {
                     return Err(ResolverError::ReadInOwnInitializer {
                         lexeme: variable.name.lexeme.clone(),
                         line: variable.name.line,
@@ -174,6 +658,192 @@ impl ExprVisitor<ResolverResult> for Resolver {
                 }
             }
         }
-        self.resolve_local(Expr::Variable(variable.clone()), &variable.name)
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Resolver;
+    use crate::{error::ResolverError, parser::Parser, scanner::Scanner};
+
+    fn resolve(source: &str) -> Result<(), ResolverError> {
+        resolver(source).map(|_| ())
+    }
+
+    fn resolver(source: &str) -> Result<Resolver, ResolverError> {
+        let mut scanner = Scanner::new(source);
+        let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+        let statements = parser.parse().expect("test program should parse");
+        let mut resolver = Resolver::new();
+        resolver.resolve(&statements)?;
+        Ok(resolver)
+    }
+
+    fn warnings(source: &str) -> Vec<String> {
+        let resolver = resolver(source).expect("test program should resolve");
+        resolver
+            .warnings()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn assigning_local_constant_is_rejected() {
+        for source in &[
+            "{ const a = 1; a = 2; }",
+            "{ const a = 1; a += 2; }",
+            "{ const a = 1; { a++; } }",
+            "fun f() { const a = 1; fun g() { a = 2; } }",
+            "{ const [a, b] = [1, 2]; [b, a] = [a, b]; }",
+            "{ const {a} = {\"a\": 1}; a = 2; }",
+        ] {
+            assert!(matches!(
+                resolve(source),
+                Err(ResolverError::AssignToConstant { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn redeclaring_local_constant_is_rejected() {
+        for source in &[
+            "{ const a = 1; var a = 2; a = 3; }",
+            "{ const a = 1; const a = 2; }",
+            "{ const a = 1; fun a() {} }",
+            "fun f() { const a = 1; var [a, b] = [2, 3]; }",
+        ] {
+            assert!(
+                matches!(
+                    resolve(source),
+                    Err(ResolverError::RedeclareConstant { line: 1, .. })
+                ),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn shadowed_constant_can_be_assigned() {
+        assert!(resolve("{ const a = 1; { var a = 2; a = 3; } }").is_ok());
+    }
+
+    #[test]
+    fn switch_cases_are_scoped_separately() {
+        assert!(resolve(
+            "{ const a = 1; switch (a) { case 1: var a = 2; a = 3; default: var a = 4; } }"
+        )
+        .is_ok());
+        assert!(matches!(
+            resolve("{ const a = 1; switch (a) { case 2: print a; default: a = 2; } }"),
+            Err(ResolverError::AssignToConstant { .. })
+        ));
+    }
+
+    #[test]
+    fn super_is_only_allowed_in_subclass_methods() {
+        assert!(resolve("class A {} class B < A { m() { return super.m(); } }").is_ok());
+        assert!(matches!(
+            resolve("class A < A {}"),
+            Err(ResolverError::InheritFromSelf { .. })
+        ));
+        assert!(matches!(
+            resolve("fun f() { return super.m(); }"),
+            Err(ResolverError::SuperOutsideClass { .. })
+        ));
+        assert!(matches!(
+            resolve("class A { m() { return super.m(); } }"),
+            Err(ResolverError::SuperWithoutSuperclass { .. })
+        ));
+        assert!(matches!(
+            resolve("trait T { m() { return super.m(); } }"),
+            Err(ResolverError::SuperInTrait { .. })
+        ));
+        assert!(matches!(
+            resolve("class A {} class B < A { class make() { return super.make(); } }"),
+            Err(ResolverError::SuperInClassMethod { .. })
+        ));
+        assert!(resolve("trait T { m() { return this.n; } }").is_ok());
+    }
+
+    #[test]
+    fn reading_local_in_own_initializer_is_rejected() {
+        assert!(matches!(
+            resolve("{ var a = 1; { var a = a + 2; } }"),
+            Err(ResolverError::ReadInOwnInitializer { .. })
+        ));
+    }
+
+    #[test]
+    fn yield_outside_function_is_rejected() {
+        assert!(matches!(
+            resolve("yield 1;"),
+            Err(ResolverError::YieldOutsideFunction { .. })
+        ));
+        assert!(matches!(
+            resolve("{ while (true) yield; }"),
+            Err(ResolverError::YieldOutsideFunction { .. })
+        ));
+        assert!(resolve("fun f() { fun g() { yield 1; } yield 2; }").is_ok());
+    }
+
+    #[test]
+    fn this_outside_class_is_rejected() {
+        assert!(matches!(
+            resolve("fun f() { return this; }"),
+            Err(ResolverError::ThisOutsideClass { .. })
+        ));
+        assert!(resolve("class A { m() { fun inner() { return this; } } }").is_ok());
+        assert!(resolve("class A { area { return this.w; } set area(v) { this.w = v; } }").is_ok());
+        assert!(matches!(
+            resolve("class A { class make() { return this; } }"),
+            Err(ResolverError::ThisInClassMethod { .. })
+        ));
+        assert!(resolve("class A { class make() { class B { m() { return this; } } } }").is_ok());
+    }
+
+    #[test]
+    fn non_exhaustive_enum_matches_are_warned_about() {
+        let shape = "enum Shape { Circle(r), Rect(w, h), Empty }\n";
+        assert_eq!(
+            warnings(&format!(
+                "{}match (Empty) {{ Circle(r) => 1; Rect(w, 0) => 2; }}",
+                shape
+            )),
+            vec!["[line 2] Match on enum 'Shape' does not cover Rect, Empty."]
+        );
+        for arms in &[
+            "Circle(r) => 1; Rect(w, h) => 2; Empty => 3;",
+            "Circle(_) => 1; _ => 2;",
+            "Empty => 1; other => 2;",
+        ] {
+            let source = format!("{}match (Empty) {{ {} }}", shape, arms);
+            assert!(warnings(&source).is_empty(), "{}", arms);
+        }
+        assert!(warnings("match (1) { 1 => 1; }").is_empty());
+        assert_eq!(
+            warnings(&format!(
+                "{}var area = match (Empty) {{ Circle(r) => r, Empty => 0 }};",
+                shape
+            ))
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn defer_needs_an_enclosing_block() {
+        assert!(matches!(
+            resolve("defer 1;"),
+            Err(ResolverError::DeferOutsideBlock { line: 1 })
+        ));
+        assert!(matches!(
+            resolve("fun numbers() { defer 1; yield 1; }"),
+            Err(ResolverError::DeferInGenerator { line: 1 })
+        ));
+        assert!(resolve("{ defer 1; } fun f() { defer 2; }").is_ok());
+        assert!(resolve("fun numbers() { fun f() { defer 1; } yield f; }").is_ok());
     }
 }
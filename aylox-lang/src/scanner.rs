@@ -1,39 +1,63 @@
 use crate::{
-    error::{AyloxError, SyntaxError},
+    error::{SyntaxError, SyntaxErrors},
     token::{Token, TokenType, Tokens, KEYWORDS},
 };
+use std::mem;
 
+/// Scans source text one `char` at a time. `start` and `current` index into `chars`,
+/// which keeps the byte offset of every character so lexemes can be sliced from `source`.
 pub struct Scanner<'a> {
     source: &'a str,
+    chars: Vec<(usize, char)>,
     tokens: Tokens,
     start: usize,
     current: usize,
     line: usize,
+    line_start: usize,
+    start_column: usize,
+    errors: Vec<SyntaxError>,
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(source: &'a str) -> Self {
         Scanner {
             source,
+            chars: source.char_indices().collect(),
             tokens: Vec::new(),
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            start_column: 1,
+            errors: Vec::new(),
         }
     }
 
     fn is_at_end(&self) -> bool {
-        self.current >= self.source.len()
+        self.current >= self.chars.len()
     }
 
-    pub fn scan_tokens(&mut self) -> &Tokens {
+    /// Scans the whole source, failing with every syntax error found once it has been read.
+    pub fn scan_tokens(&mut self) -> Result<&Tokens, SyntaxErrors> {
         while !self.is_at_end() {
             self.start = self.current;
+            self.start_column = self.start - self.line_start + 1;
             self.scan_token()
         }
 
-        self.tokens.push(Token::new(TokenType::Eof, "", self.line));
-        &self.tokens
+        self.start = self.current;
+        self.start_column = self.start - self.line_start + 1;
+        self.tokens.push(Token::with_column(
+            TokenType::Eof,
+            "",
+            self.line,
+            self.column(),
+        ));
+        if self.errors.is_empty() {
+            Ok(&self.tokens)
+        } else {
+            Err(SyntaxErrors(mem::take(&mut self.errors)))
+        }
     }
 
     fn scan_token(&mut self) {
@@ -43,12 +67,64 @@ impl<'a> Scanner<'a> {
             ')' => self.add_token(TokenType::RightParen),
             '{' => self.add_token(TokenType::LeftBrace),
             '}' => self.add_token(TokenType::RightBrace),
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
+            '.' => {
+                if self.match_next('.') {
+                    if self.match_next('.') {
+                        self.add_token(TokenType::DotDotDot)
+                    } else if self.match_next('=') {
+                        self.add_token(TokenType::DotDotEqual)
+                    } else {
+                        self.add_token(TokenType::DotDot)
+                    }
+                } else {
+                    self.add_token(TokenType::Dot)
+                }
+            }
+            '-' => {
+                if self.match_next('=') {
+                    self.add_token(TokenType::MinusEqual)
+                } else if self.match_next('-') {
+                    self.add_token(TokenType::MinusMinus)
+                } else {
+                    self.add_token(TokenType::Minus)
+                }
+            }
+            '+' => {
+                if self.match_next('=') {
+                    self.add_token(TokenType::PlusEqual)
+                } else if self.match_next('+') {
+                    self.add_token(TokenType::PlusPlus)
+                } else {
+                    self.add_token(TokenType::Plus)
+                }
+            }
             ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
+            '@' => self.add_token(TokenType::At),
+            '%' => self.add_token(TokenType::Percent),
+            '?' => {
+                if self.match_next('?') {
+                    self.add_token(TokenType::QuestionQuestion)
+                } else if self.match_next('.') {
+                    self.add_token(TokenType::QuestionDot)
+                } else {
+                    self.add_token(TokenType::Question)
+                }
+            }
+            ':' => self.add_token(TokenType::Colon),
+            '|' if self.match_next('>') => self.add_token(TokenType::PipeGreater),
+            '~' if self.match_next('/') => self.add_token(TokenType::TildeSlash),
+            '*' => {
+                if self.match_next('*') {
+                    self.add_token(TokenType::StarStar)
+                } else if self.match_next('=') {
+                    self.add_token(TokenType::StarEqual)
+                } else {
+                    self.add_token(TokenType::Star)
+                }
+            }
             '!' => {
                 if self.match_next('=') {
                     self.add_token(TokenType::BangEqual)
@@ -59,6 +135,8 @@ impl<'a> Scanner<'a> {
             '=' => {
                 if self.match_next('=') {
                     self.add_token(TokenType::EqualEqual)
+                } else if self.match_next('>') {
+                    self.add_token(TokenType::FatArrow)
                 } else {
                     self.add_token(TokenType::Equal)
                 }
@@ -85,63 +163,160 @@ impl<'a> Scanner<'a> {
                     }
                 } else if self.match_next('*') {
                     self.multi_line_comment()
+                } else if self.match_next('=') {
+                    self.add_token(TokenType::SlashEqual)
                 } else {
                     self.add_token(TokenType::Slash)
                 }
             }
             ' ' | '\r' | '\t' => {}
-            '\n' => self.line += 1,
+            '\n' => self.newline(),
             '"' => self.string(),
             _other => {
                 if _other.is_ascii_digit() {
                     self.number()
-                } else if _other.is_ascii_alphabetic() || _other == '_' {
+                } else if is_identifier_start(_other) {
                     self.identifier()
                 } else {
-                    println!(
-                        "{}",
-                        AyloxError::SyntaxError(SyntaxError::UnexpectedToken {
-                            line: self.line,
-                            found: _other.into()
-                        })
-                    );
+                    self.errors.push(SyntaxError::UnexpectedToken {
+                        line: self.line,
+                        column: self.column(),
+                        found: _other.into(),
+                    });
                 }
             }
         }
     }
 
+    /// Scans decimal integers and floats (`1_000`, `6.02e23`) as well as hexadecimal,
+    /// octal and binary integers (`0xFF`, `0o17`, `0b1010`). Underscores may separate digits.
     fn number(&mut self) {
-        while self.peek().is_ascii_digit() {
+        let first = self.chars[self.start].1;
+        let radix = match self.peek() {
+            'x' | 'X' if first == '0' => Some(16),
+            'o' | 'O' if first == '0' => Some(8),
+            'b' | 'B' if first == '0' => Some(2),
+            _ => None,
+        };
+        if let Some(radix) = radix {
             self.advance();
+            return self.radix_number(radix);
         }
 
+        self.digits();
+
+        let mut is_float = false;
         if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+            is_float = true;
             self.advance();
+            self.digits();
+        }
 
-            while self.peek().is_ascii_digit() {
+        if self.peek() == 'e' || self.peek() == 'E' {
+            is_float = true;
+            self.advance();
+            if self.peek() == '+' || self.peek() == '-' {
                 self.advance();
             }
+            if !self.peek().is_ascii_digit() {
+                return self.malformed_number("missing exponent digits");
+            }
+            self.digits();
         }
 
-        let value = self
-            .source
-            .get(self.start..self.current)
-            .expect("could not find number");
-        self.add_token_literal(TokenType::Number(
-            value
-                .parse::<f64>()
-                .expect("could not read number from string"),
-        ))
+        let literal = self.lexeme().to_string();
+        if let Err(msg) = check_separators(&literal, 10) {
+            return self.malformed_number(msg);
+        }
+        let digits = literal.replace('_', "");
+        if is_float {
+            match digits.parse::<f64>() {
+                Ok(float) => self.add_token_literal(TokenType::Number(float)),
+                Err(_) => self.malformed_number("invalid float"),
+            }
+        } else {
+            self.integer(&digits, 10)
+        }
     }
 
-    fn identifier(&mut self) {
+    fn radix_number(&mut self, radix: u32) {
         while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
             self.advance();
         }
-        let text = self
-            .source
-            .get(self.start..self.current)
-            .expect("could not read rest of token");
+
+        let literal = self.lexeme().to_string();
+        let body = &literal[2..];
+        if body.replace('_', "").is_empty() {
+            return self.malformed_number("missing digits after base prefix");
+        }
+        if body.chars().any(|c| c != '_' && !c.is_digit(radix)) {
+            return self.malformed_number(match radix {
+                16 => "invalid hexadecimal digit",
+                8 => "invalid octal digit",
+                _ => "invalid binary digit",
+            });
+        }
+        if let Err(msg) = check_separators(body, radix) {
+            return self.malformed_number(msg);
+        }
+        self.integer(&body.replace('_', ""), radix)
+    }
+
+    fn digits(&mut self) {
+        while self.peek().is_ascii_digit() || self.peek() == '_' {
+            self.advance();
+        }
+    }
+
+    fn integer(&mut self, digits: &str, radix: u32) {
+        if let Ok(integer) = i64::from_str_radix(digits, radix) {
+            self.add_token_literal(TokenType::Integer(integer))
+        } else {
+            self.errors.push(SyntaxError::IntegerTooLarge {
+                line: self.line,
+                column: self.column(),
+                literal: self.lexeme().to_string(),
+            });
+        }
+    }
+
+    fn malformed_number(&mut self, msg: &str) {
+        self.errors.push(SyntaxError::MalformedNumber {
+            line: self.line,
+            column: self.column(),
+            literal: self.lexeme().to_string(),
+            msg: msg.to_string(),
+        });
+    }
+
+    fn lexeme(&self) -> &str {
+        self.source
+            .get(self.offset(self.start)..self.offset(self.current))
+            .expect("could not read rest of token")
+    }
+
+    /// Byte offset into `source` of the character at `index`, or the source length past the end.
+    fn offset(&self, index: usize) -> usize {
+        self.chars
+            .get(index)
+            .map_or(self.source.len(), |(offset, _)| *offset)
+    }
+
+    /// 1-based column, counted in characters, where the token being scanned starts.
+    fn column(&self) -> usize {
+        self.start_column
+    }
+
+    fn newline(&mut self) {
+        self.line += 1;
+        self.line_start = self.current;
+    }
+
+    fn identifier(&mut self) {
+        while is_identifier_continue(self.peek()) {
+            self.advance();
+        }
+        let text = self.lexeme();
         let token_type = KEYWORDS.get(text);
         if let Some(token_type) = token_type {
             self.add_token(token_type.to_owned())
@@ -152,17 +327,16 @@ impl<'a> Scanner<'a> {
 
     fn string(&mut self) {
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1
+            if self.advance() == '\n' {
+                self.newline();
             }
-            self.advance();
         }
 
         if self.is_at_end() {
-            println!(
-                "{}",
-                AyloxError::SyntaxError(SyntaxError::UnterminatedString { line: self.line })
-            );
+            self.errors.push(SyntaxError::UnterminatedString {
+                line: self.line,
+                column: self.column(),
+            });
             return;
         }
 
@@ -172,20 +346,17 @@ impl<'a> Scanner<'a> {
         // trim surrounding quotes
         let value = self
             .source
-            .get(self.start + 1..self.current - 1)
+            .get(self.offset(self.start + 1)..self.offset(self.current - 1))
             .expect("could not trim quotes");
         self.add_token_literal(TokenType::String(value.to_owned()))
     }
 
     fn multi_line_comment(&mut self) {
-        while self.peek_n(2) != "*/" && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
-            }
-            if let '/' = self.advance() {
-                if self.match_next('*') {
-                    self.multi_line_comment()
-                }
+        while (self.peek() != '*' || self.peek_next() != '/') && !self.is_at_end() {
+            match self.advance() {
+                '\n' => self.newline(),
+                '/' if self.match_next('*') => self.multi_line_comment(),
+                _ => {}
             }
         }
         if self.is_at_end() {
@@ -197,35 +368,15 @@ impl<'a> Scanner<'a> {
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            '\0'
-        } else {
-            self.source.as_bytes()[self.current].into()
-        }
+        self.chars.get(self.current).map_or('\0', |(_, c)| *c)
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        }
-        return self.source.as_bytes()[self.current + 1].into();
-    }
-
-    fn peek_n(&self, n: usize) -> &str {
-        if self.current + n >= self.source.len() {
-            return "\0";
-        }
-        return self
-            .source
-            .get(self.current..self.current + n)
-            .unwrap_or("\0");
+        self.chars.get(self.current + 1).map_or('\0', |(_, c)| *c)
     }
 
     fn match_next(&mut self, expected: char) -> bool {
-        if self.is_at_end() {
-            return false;
-        }
-        if self.source.as_bytes()[self.current] as char != expected {
+        if self.peek() != expected || self.is_at_end() {
             return false;
         }
         self.current += 1;
@@ -234,53 +385,401 @@ impl<'a> Scanner<'a> {
 
     fn advance(&mut self) -> char {
         self.current += 1;
-        self.source.as_bytes()[self.current - 1].into()
+        self.chars[self.current - 1].1
     }
 
     fn add_token(&mut self, token_type: TokenType) {
-        let text = self
-            .source
-            .get(self.start..self.current)
-            .expect("could not read rest of token");
-        self.tokens.push(Token::new(token_type, text, self.line));
+        let token = Token::with_column(token_type, self.lexeme(), self.line, self.column());
+        self.tokens.push(token);
     }
 
     fn add_token_literal(&mut self, token_type: TokenType) {
-        let text = self
-            .source
-            .get(self.start..self.current)
-            .expect("could not read rest of token");
         match token_type {
-            TokenType::String(literal) => {
-                self.tokens
-                    .push(Token::new(TokenType::String(literal), text, self.line))
-            }
-            TokenType::Identifier(literal) => {
-                self.tokens
-                    .push(Token::new(TokenType::Identifier(literal), text, self.line))
-            }
-            TokenType::Number(literal) => {
-                self.tokens
-                    .push(Token::new(TokenType::Number(literal), text, self.line))
-            }
+            TokenType::String(_)
+            | TokenType::Identifier(_)
+            | TokenType::Number(_)
+            | TokenType::Integer(_) => self.add_token(token_type),
             _ => {}
         }
     }
 }
 
+/// Identifiers are ASCII letters, digits and `_` by default. With the `unicode-identifiers`
+/// feature they follow Unicode's `XID_Start`/`XID_Continue` classes, so `π` or `名前` are names.
+#[cfg(not(feature = "unicode-identifiers"))]
+fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+#[cfg(not(feature = "unicode-identifiers"))]
+fn is_identifier_continue(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+#[cfg(feature = "unicode-identifiers")]
+fn is_identifier_start(c: char) -> bool {
+    unicode_xid::UnicodeXID::is_xid_start(c) || c == '_'
+}
+
+#[cfg(feature = "unicode-identifiers")]
+fn is_identifier_continue(c: char) -> bool {
+    unicode_xid::UnicodeXID::is_xid_continue(c)
+}
+
+/// Digit separators must sit between two digits: `1_000` is fine, `1__0`, `1_` and `1_.5` are not.
+fn check_separators(literal: &str, radix: u32) -> Result<(), &'static str> {
+    let chars: Vec<char> = literal.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        if *c == '_' {
+            // a prefixed literal may start with a separator, as in `0x_FF`
+            let before = match i.checked_sub(1) {
+                Some(before) => chars[before].is_digit(radix),
+                None => radix != 10,
+            };
+            let after = chars.get(i + 1).is_some_and(|after| after.is_digit(radix));
+            if !before || !after {
+                return Err("'_' must separate digits");
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::Scanner;
+    use crate::{error::SyntaxError, token::TokenType};
 
     #[test]
     fn nested_multi_line_comment() {
         let input = "/* /* ... */ */";
         let mut scanner = Scanner::new(input);
-        let output = scanner.scan_tokens();
+        let output = scanner.scan_tokens().unwrap();
 
         // println!("{:#?}", output);
 
         assert_eq!(output.len(), 1);
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn tilde_slash_is_integer_division() {
+        let input = "var a = 7 ~/ 2; // a comment\nprint (a) ~/ 2;";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        let types: Vec<TokenType> = output.iter().map(|t| t._type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Var,
+                TokenType::Identifier("a".to_string()),
+                TokenType::Equal,
+                TokenType::Integer(7),
+                TokenType::TildeSlash,
+                TokenType::Integer(2),
+                TokenType::Semicolon,
+                TokenType::Print,
+                TokenType::LeftParen,
+                TokenType::Identifier("a".to_string()),
+                TokenType::RightParen,
+                TokenType::TildeSlash,
+                TokenType::Integer(2),
+                TokenType::Semicolon,
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn double_slash_after_operand_is_a_comment() {
+        let input =
+            "if (x) // only when x\n[1] // one\n{\"a\": 1 // first\n}\nvar a = 1 // note\n+ 2;";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        let types: Vec<TokenType> = output.iter().map(|t| t._type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::If,
+                TokenType::LeftParen,
+                TokenType::Identifier("x".to_string()),
+                TokenType::RightParen,
+                TokenType::LeftBracket,
+                TokenType::Integer(1),
+                TokenType::RightBracket,
+                TokenType::LeftBrace,
+                TokenType::String("a".to_string()),
+                TokenType::Colon,
+                TokenType::Integer(1),
+                TokenType::RightBrace,
+                TokenType::Var,
+                TokenType::Identifier("a".to_string()),
+                TokenType::Equal,
+                TokenType::Integer(1),
+                TokenType::Plus,
+                TokenType::Integer(2),
+                TokenType::Semicolon,
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn compound_assignment_tokens() {
+        let input = "a += 1; a -= 1; a *= 2; a /= 2; a++; --a;";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        let operators: Vec<TokenType> = output
+            .iter()
+            .map(|t| t._type.clone())
+            .filter(|t| {
+                !matches!(
+                    t,
+                    TokenType::Identifier(_)
+                        | TokenType::Integer(_)
+                        | TokenType::Semicolon
+                        | TokenType::Eof
+                )
+            })
+            .collect();
+        assert_eq!(
+            operators,
+            vec![
+                TokenType::PlusEqual,
+                TokenType::MinusEqual,
+                TokenType::StarEqual,
+                TokenType::SlashEqual,
+                TokenType::PlusPlus,
+                TokenType::MinusMinus,
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_and_float_literals() {
+        let input = "3 3.0 9223372036854775807";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        assert_eq!(output[0]._type, TokenType::Integer(3));
+        assert_eq!(output[1]._type, TokenType::Number(3.0));
+        assert_eq!(output[2]._type, TokenType::Integer(i64::MAX));
+    }
+
+    #[test]
+    fn extended_numeric_literals() {
+        let input = "0xFF 0Xff 0b1010 0o17 1_000_000 6.02e23 1E-3 2.5e+2 0x7FFF_FFFF";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        let types: Vec<TokenType> = output.iter().map(|t| t._type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Integer(255),
+                TokenType::Integer(255),
+                TokenType::Integer(10),
+                TokenType::Integer(15),
+                TokenType::Integer(1_000_000),
+                TokenType::Number(6.02e23),
+                TokenType::Number(1e-3),
+                TokenType::Number(250.0),
+                TokenType::Integer(0x7FFF_FFFF),
+                TokenType::Eof,
+            ]
+        );
+        assert_eq!(output[4].lexeme, "1_000_000");
+    }
+
+    #[test]
+    fn malformed_numeric_literals_fail_the_scan() {
+        for input in &[
+            "0x", "0b", "1e", "1e+", "0b102", "0o8", "0xFG", "1__0", "1_", "0x_",
+        ] {
+            let mut scanner = Scanner::new(input);
+            let errors = scanner.scan_tokens().unwrap_err().0;
+            assert!(
+                matches!(errors[..], [SyntaxError::MalformedNumber { .. }]),
+                "'{}' should be malformed, found {:?}",
+                input,
+                errors
+            );
+        }
+
+        let mut scanner = Scanner::new("var a = 0x;\nvar b = 9223372036854775808;");
+        let errors = scanner.scan_tokens().unwrap_err().0;
+        assert!(matches!(
+            errors[..],
+            [
+                SyntaxError::MalformedNumber {
+                    line: 1,
+                    column: 9,
+                    ..
+                },
+                SyntaxError::IntegerTooLarge { line: 2, .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn power_and_modulo_tokens() {
+        let input = "2 ** 3 % 4 * 5";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        assert_eq!(output[1]._type, TokenType::StarStar);
+        assert_eq!(output[3]._type, TokenType::Percent);
+        assert_eq!(output[5]._type, TokenType::Star);
+    }
+
+    #[test]
+    fn multi_byte_characters_in_strings_and_comments() {
+        let input = "// ça va? ✓\nprint \"héllo, 世界 🦀\"; /* ünïcödé */ 1";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        let types: Vec<TokenType> = output.iter().map(|t| t._type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Print,
+                TokenType::String("héllo, 世界 🦀".to_string()),
+                TokenType::Semicolon,
+                TokenType::Integer(1),
+                TokenType::Eof,
+            ]
+        );
+        assert_eq!(output[1].lexeme, "\"héllo, 世界 🦀\"");
+    }
+
+    #[test]
+    fn columns_are_counted_in_characters() {
+        let input = "var s = \"日本\"; s\n  print s;";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        let positions: Vec<(usize, usize)> = output.iter().map(|t| (t.line, t.column)).collect();
+        assert_eq!(
+            positions,
+            vec![
+                (1, 1),
+                (1, 5),
+                (1, 7),
+                (1, 9),
+                (1, 13),
+                (1, 15),
+                (2, 3),
+                (2, 9),
+                (2, 10),
+                (2, 11),
+            ]
+        );
+    }
+
+    #[cfg(feature = "unicode-identifiers")]
+    #[test]
+    fn unicode_identifiers() {
+        let input = "var π = 3; var 名前_1 = π;";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        assert_eq!(output[1]._type, TokenType::Identifier("π".to_string()));
+        assert_eq!(output[6]._type, TokenType::Identifier("名前_1".to_string()));
+        assert_eq!(output[8]._type, TokenType::Identifier("π".to_string()));
+    }
+
+    #[cfg(not(feature = "unicode-identifiers"))]
+    #[test]
+    fn non_ascii_identifiers_are_rejected_by_default() {
+        let input = "π";
+        let mut scanner = Scanner::new(input);
+        let errors = scanner.scan_tokens().unwrap_err().0;
+
+        assert!(matches!(
+            &errors[..],
+            [SyntaxError::UnexpectedToken { found, .. }] if found == "π"
+        ));
+    }
+
+    #[test]
+    fn collection_and_range_tokens() {
+        let input = "for (x in [1, 2]) 0..3.5 1..=2";
+        let mut scanner = Scanner::new(input);
+        let output = scanner.scan_tokens().unwrap();
+
+        let types: Vec<TokenType> = output.iter().map(|t| t._type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::For,
+                TokenType::LeftParen,
+                TokenType::Identifier("x".to_string()),
+                TokenType::In,
+                TokenType::LeftBracket,
+                TokenType::Integer(1),
+                TokenType::Comma,
+                TokenType::Integer(2),
+                TokenType::RightBracket,
+                TokenType::RightParen,
+                TokenType::Integer(0),
+                TokenType::DotDot,
+                TokenType::Number(3.5),
+                TokenType::Integer(1),
+                TokenType::DotDotEqual,
+                TokenType::Integer(2),
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_and_match_tokens() {
+        let mut scanner = Scanner::new("enum match => ==>");
+        let types: Vec<TokenType> = scanner
+            .scan_tokens()
+            .unwrap()
+            .iter()
+            .map(|t| t._type.clone())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Enum,
+                TokenType::Match,
+                TokenType::FatArrow,
+                TokenType::EqualEqual,
+                TokenType::Greater,
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn question_mark_tokens() {
+        let mut scanner = Scanner::new("a ?? b?.c ? d |> e");
+        let types: Vec<TokenType> = scanner
+            .scan_tokens()
+            .unwrap()
+            .iter()
+            .map(|t| t._type.clone())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Identifier("a".to_string()),
+                TokenType::QuestionQuestion,
+                TokenType::Identifier("b".to_string()),
+                TokenType::QuestionDot,
+                TokenType::Identifier("c".to_string()),
+                TokenType::Question,
+                TokenType::Identifier("d".to_string()),
+                TokenType::PipeGreater,
+                TokenType::Identifier("e".to_string()),
+                TokenType::Eof,
+            ]
+        );
+    }
 }
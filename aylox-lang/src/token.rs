@@ -6,14 +6,21 @@ pub struct Token {
     pub _type: TokenType,
     pub lexeme: String,
     pub line: usize,
+    /// 1-based column of the token's first character, or 0 for tokens not read from source.
+    pub column: usize,
 }
 
 impl Token {
     pub fn new(token_type: TokenType, lexeme: &str, line: usize) -> Self {
+        Token::with_column(token_type, lexeme, line, 0)
+    }
+
+    pub fn with_column(token_type: TokenType, lexeme: &str, line: usize, column: usize) -> Self {
         Token {
             _type: token_type,
             lexeme: lexeme.to_owned(),
             line,
+            column,
         }
     }
 }
@@ -25,6 +32,8 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
     Minus,
@@ -32,6 +41,13 @@ pub enum TokenType {
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Question,
+    QuestionQuestion,
+    QuestionDot,
+    PipeGreater,
+    At,
+    Colon,
 
     // one or two char tokens
     Bang,
@@ -42,11 +58,24 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    TildeSlash,
+    StarStar,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PlusPlus,
+    MinusMinus,
+    DotDot,
+    DotDotEqual,
+    DotDotDot,
+    FatArrow,
 
     // literals
     Identifier(String),
     String(String),
     Number(f64),
+    Integer(i64),
 
     // Keywords
     And,
@@ -63,8 +92,26 @@ pub enum TokenType {
     This,
     True,
     Var,
+    Const,
     While,
     Print,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    Import,
+    From,
+    As,
+    In,
+    Yield,
+    Enum,
+    Match,
+    Switch,
+    Case,
+    Default,
+    Trait,
+    With,
+    Defer,
 
     Eof,
 }
@@ -84,6 +131,24 @@ pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "this" => TokenType::This,
     "true" => TokenType::True,
     "var" => TokenType::Var,
+    "const" => TokenType::Const,
     "while" => TokenType::While,
-    "print" => TokenType::Print
+    "print" => TokenType::Print,
+    "throw" => TokenType::Throw,
+    "try" => TokenType::Try,
+    "catch" => TokenType::Catch,
+    "finally" => TokenType::Finally,
+    "import" => TokenType::Import,
+    "from" => TokenType::From,
+    "as" => TokenType::As,
+    "in" => TokenType::In,
+    "yield" => TokenType::Yield,
+    "enum" => TokenType::Enum,
+    "match" => TokenType::Match,
+    "switch" => TokenType::Switch,
+    "case" => TokenType::Case,
+    "default" => TokenType::Default,
+    "trait" => TokenType::Trait,
+    "with" => TokenType::With,
+    "defer" => TokenType::Defer
 };
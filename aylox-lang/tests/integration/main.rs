@@ -1 +1,115 @@
+use std::{fs, path::PathBuf};
 
+use aylox_lang::{
+    ast::{AloxObject, Value},
+    error::RuntimeException,
+    interpreter::Interpreter,
+    parser::Parser,
+    scanner::Scanner,
+    token::{Token, TokenType},
+};
+
+fn program_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/integration/test_programs")
+        .join(name)
+}
+
+fn run_program(name: &str) -> (Interpreter, Result<(), RuntimeException>) {
+    let path = program_path(name);
+    let contents = fs::read_to_string(&path).unwrap();
+    let mut scanner = Scanner::new(&contents);
+    let mut parser = Parser::new(scanner.scan_tokens().expect("test program should scan"));
+    let statements = parser.parse().unwrap();
+    let mut interpreter = Interpreter::with_script_path(&path);
+    let result = interpreter.interpret(&statements);
+    (interpreter, result)
+}
+
+fn global(interpreter: &Interpreter, name: &str) -> Value {
+    let token = Token::new(TokenType::Identifier(name.to_string()), name, 0);
+    match interpreter
+        .global_env
+        .borrow()
+        .get(&token)
+        .unwrap()
+        .as_ref()
+    {
+        Some(AloxObject::Value(value)) => value.clone(),
+        other => panic!("expected a value for '{}', found {:?}", name, other),
+    }
+}
+
+#[test]
+fn imports_resolve_relative_to_importing_file() {
+    let (interpreter, result) = run_program("modules/main.lox");
+    result.unwrap();
+    assert_eq!(global(&interpreter, "area"), Value::Integer(12));
+    assert_eq!(global(&interpreter, "cubed"), Value::Integer(27));
+    assert_eq!(global(&interpreter, "destructured"), Value::Integer(8));
+}
+
+#[test]
+fn warnings_in_imported_modules_are_left_to_the_caller() {
+    let (interpreter, result) = run_program("modules/warnings.lox");
+    result.unwrap();
+    assert_eq!(global(&interpreter, "squares"), Value::Integer(4));
+    let warnings: Vec<String> = interpreter
+        .warnings()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        warnings,
+        vec!["[line 4] Match on enum 'Shape' does not cover Circle."]
+    );
+}
+
+#[test]
+fn modules_print_as_their_path() {
+    let (interpreter, result) = run_program("modules/print.lox");
+    result.unwrap();
+    let token = Token::new(TokenType::Identifier("math".to_string()), "math", 0);
+    let math = interpreter.global_env.borrow().get(&token).unwrap();
+    match math.as_ref() {
+        Some(AloxObject::Module(module)) => {
+            let shown = module.to_string();
+            assert!(shown.starts_with("<module "), "{}", shown);
+            assert!(shown.ends_with("lib/math.lox>"), "{}", shown);
+        }
+        other => panic!("expected a module for 'math', found {:?}", other),
+    }
+}
+
+#[test]
+fn modules_are_evaluated_once() {
+    let (interpreter, result) = run_program("modules/main.lox");
+    result.unwrap();
+    assert_eq!(global(&interpreter, "counted"), Value::Integer(1));
+}
+
+#[test]
+fn import_cycles_are_reported() {
+    let (_, result) = run_program("modules/cycle_a.lox");
+    match result {
+        Err(RuntimeException::ImportCycle { cycle, line }) => {
+            assert_eq!(line, 1);
+            let modules: Vec<&str> = cycle
+                .split(" -> ")
+                .map(|path| path.rsplit('/').next().unwrap())
+                .collect();
+            assert_eq!(modules, vec!["cycle_a.lox", "cycle_b.lox", "cycle_a.lox"]);
+        }
+        other => panic!("expected an import cycle, found {:?}", other),
+    }
+}
+
+#[test]
+fn for_in_uses_the_iterator_protocol() {
+    let (interpreter, result) = run_program("iteration/main.lox");
+    result.unwrap();
+    assert_eq!(
+        global(&interpreter, "order"),
+        Value::String("321".to_string())
+    );
+}